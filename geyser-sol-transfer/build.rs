@@ -176,7 +176,7 @@ message SubscribeUpdateBlockMeta {
     tonic_build::configure()
         .build_server(false)
         .out_dir(&out_dir)
-        .compile(&[proto_path], &[out_dir])?;
+        .compile_protos(&[proto_path], &[out_dir])?;
 
     println!("cargo:rerun-if-changed=build.rs");
 
@@ -0,0 +1,395 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use crate::geyser::{SubscribeRequestFilterAccounts, SubscribeUpdateAccount};
+use crate::triggers::Action;
+
+/// Accounts to subscribe to, by address or by owning program.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct WatchConfig {
+    #[serde(default)]
+    pub accounts: Vec<String>,
+    #[serde(default)]
+    pub owners: Vec<String>,
+}
+
+impl WatchConfig {
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty() && self.owners.is_empty()
+    }
+
+    pub fn filter(&self) -> SubscribeRequestFilterAccounts {
+        SubscribeRequestFilterAccounts {
+            account: self.accounts.clone(),
+            owner: self.owners.clone(),
+            filters: false,
+        }
+    }
+
+    /// Whether `event` matches this filter rather than only another
+    /// subscription on the same stream, such as the sweeper's.
+    pub fn watches(&self, event: &AccountEvent) -> bool {
+        let pubkey = event.pubkey.to_string();
+        let owner = event.owner.to_string();
+        self.accounts.contains(&pubkey) || self.owners.contains(&owner)
+    }
+}
+
+/// A named rule run against updates of watched accounts.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AccountRule {
+    pub name: String,
+    /// Restricts the rule to one watched account.
+    #[serde(default)]
+    pub account: Option<String>,
+    #[serde(with = "serde_yaml::with::singleton_map")]
+    pub condition: AccountCondition,
+    #[serde(default, with = "serde_yaml::with::singleton_map")]
+    pub action: Action,
+}
+
+/// When an account rule fires. Conditions compare an update with the previous
+/// one seen for the same account, so the first update only sets a baseline.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountCondition {
+    /// Lamports rise from at most to above this threshold.
+    LamportsAbove(u64),
+    /// Lamports drop from at least to below this threshold.
+    LamportsBelow(u64),
+    /// Account data differs from the previous update.
+    DataChanged,
+}
+
+/// A `SubscribeUpdateAccount` with its raw fields decoded.
+#[derive(Debug, Clone)]
+pub struct AccountEvent {
+    pub pubkey: Pubkey,
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub slot: u64,
+    pub write_version: u64,
+    pub txn_signature: Option<Signature>,
+    pub is_startup: bool,
+}
+
+impl TryFrom<SubscribeUpdateAccount> for AccountEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(update: SubscribeUpdateAccount) -> Result<Self> {
+        let info = update
+            .account
+            .ok_or_else(|| anyhow!("Err: account update without account"))?;
+        let txn_signature = if info.txn_signature.is_empty() {
+            None
+        } else {
+            Some(Signature::try_from(info.txn_signature.as_slice()).context("Err: signature")?)
+        };
+
+        Ok(Self {
+            pubkey: Pubkey::try_from(info.pubkey.as_slice()).context("Err: account pubkey")?,
+            owner: Pubkey::try_from(info.owner.as_slice()).context("Err: account owner")?,
+            lamports: info.lamports,
+            data: info.data,
+            slot: update.slot,
+            write_version: info.write_version,
+            txn_signature,
+            is_startup: update.is_startup,
+        })
+    }
+}
+
+/// Evaluates account rules, remembering the last state of every account.
+pub struct AccountTriggers {
+    rules: Vec<(AccountRule, Option<Pubkey>)>,
+    seen: HashMap<Pubkey, Seen>,
+}
+
+#[derive(Clone, Copy)]
+struct Seen {
+    lamports: u64,
+    data_hash: u64,
+    write_version: u64,
+}
+
+impl AccountTriggers {
+    pub fn new(rules: Vec<AccountRule>) -> Result<Self> {
+        let rules = rules
+            .into_iter()
+            .map(|rule| {
+                let account = rule
+                    .account
+                    .as_deref()
+                    .map(Pubkey::from_str)
+                    .transpose()
+                    .with_context(|| format!("Err: account of rule {}", rule.name))?;
+                Ok((rule, account))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            rules,
+            seen: HashMap::new(),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Returns the rules `event` fires, in config order. Updates older than
+    /// the last one seen for the account are ignored.
+    pub fn evaluate(&mut self, event: &AccountEvent) -> Vec<&AccountRule> {
+        let now = Seen {
+            lamports: event.lamports,
+            data_hash: hash(&event.data),
+            write_version: event.write_version,
+        };
+        let previous = self.seen.get(&event.pubkey).copied();
+        if previous.is_some_and(|previous| previous.write_version > now.write_version) {
+            return Vec::new();
+        }
+        self.seen.insert(event.pubkey, now);
+        let Some(previous) = previous else {
+            return Vec::new();
+        };
+
+        self.rules
+            .iter()
+            .filter(|(_, account)| account.is_none_or(|account| account == event.pubkey))
+            .filter(|(rule, _)| rule.condition.matches(&previous, &now))
+            .map(|(rule, _)| rule)
+            .collect()
+    }
+}
+
+impl AccountCondition {
+    fn matches(&self, previous: &Seen, now: &Seen) -> bool {
+        match self {
+            AccountCondition::LamportsAbove(threshold) => {
+                previous.lamports <= *threshold && now.lamports > *threshold
+            }
+            AccountCondition::LamportsBelow(threshold) => {
+                previous.lamports >= *threshold && now.lamports < *threshold
+            }
+            AccountCondition::DataChanged => previous.data_hash != now.data_hash,
+        }
+    }
+}
+
+fn hash(data: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(pubkey: Pubkey, owner: Pubkey) -> AccountEvent {
+        AccountEvent {
+            pubkey,
+            owner,
+            lamports: 0,
+            data: vec![],
+            slot: 0,
+            write_version: 0,
+            txn_signature: None,
+            is_startup: false,
+        }
+    }
+
+    fn update(pubkey: Pubkey, lamports: u64, data: &[u8], write_version: u64) -> AccountEvent {
+        AccountEvent {
+            lamports,
+            data: data.to_vec(),
+            write_version,
+            ..event(pubkey, Pubkey::default())
+        }
+    }
+
+    fn rule(name: &str, account: Option<&Pubkey>, condition: AccountCondition) -> AccountRule {
+        AccountRule {
+            name: name.to_string(),
+            account: account.map(ToString::to_string),
+            condition,
+            action: Action::Log,
+        }
+    }
+
+    fn fired(triggers: &mut AccountTriggers, event: &AccountEvent) -> Vec<String> {
+        triggers
+            .evaluate(event)
+            .into_iter()
+            .map(|rule| rule.name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn first_update_only_sets_the_baseline() {
+        let account = Pubkey::new_unique();
+        let mut triggers = AccountTriggers::new(vec![
+            rule("above", None, AccountCondition::LamportsAbove(100)),
+            rule("changed", None, AccountCondition::DataChanged),
+        ])
+        .unwrap();
+
+        assert!(fired(&mut triggers, &update(account, 500, b"a", 1)).is_empty());
+        assert!(fired(&mut triggers, &update(account, 500, b"a", 2)).is_empty());
+        // Each account keeps its own baseline.
+        let other = Pubkey::new_unique();
+        assert!(fired(&mut triggers, &update(other, 500, b"b", 1)).is_empty());
+    }
+
+    #[test]
+    fn lamport_thresholds_fire_when_crossed_in_their_direction() {
+        let account = Pubkey::new_unique();
+        let mut triggers = AccountTriggers::new(vec![
+            rule("above", None, AccountCondition::LamportsAbove(100)),
+            rule("below", None, AccountCondition::LamportsBelow(100)),
+        ])
+        .unwrap();
+
+        fired(&mut triggers, &update(account, 50, &[], 1));
+        assert_eq!(
+            fired(&mut triggers, &update(account, 101, &[], 2)),
+            ["above"]
+        );
+        assert!(fired(&mut triggers, &update(account, 200, &[], 3)).is_empty());
+        assert!(fired(&mut triggers, &update(account, 100, &[], 4)).is_empty());
+        assert_eq!(
+            fired(&mut triggers, &update(account, 99, &[], 5)),
+            ["below"]
+        );
+        assert!(fired(&mut triggers, &update(account, 10, &[], 6)).is_empty());
+        // Exactly at the threshold is neither above nor below it.
+        assert!(fired(&mut triggers, &update(account, 100, &[], 7)).is_empty());
+        assert_eq!(
+            fired(&mut triggers, &update(account, 101, &[], 8)),
+            ["above"]
+        );
+    }
+
+    #[test]
+    fn data_changed_compares_with_the_previous_update() {
+        let account = Pubkey::new_unique();
+        let mut triggers =
+            AccountTriggers::new(vec![rule("changed", None, AccountCondition::DataChanged)])
+                .unwrap();
+
+        fired(&mut triggers, &update(account, 1, b"one", 1));
+        assert!(fired(&mut triggers, &update(account, 2, b"one", 2)).is_empty());
+        assert_eq!(
+            fired(&mut triggers, &update(account, 2, b"two", 3)),
+            ["changed"]
+        );
+        assert!(fired(&mut triggers, &update(account, 2, b"two", 4)).is_empty());
+    }
+
+    #[test]
+    fn out_of_order_updates_are_ignored() {
+        let account = Pubkey::new_unique();
+        let mut triggers = AccountTriggers::new(vec![
+            rule("above", None, AccountCondition::LamportsAbove(100)),
+            rule("below", None, AccountCondition::LamportsBelow(100)),
+        ])
+        .unwrap();
+
+        fired(&mut triggers, &update(account, 50, &[], 5));
+        // An older write neither fires nor becomes the baseline.
+        assert!(fired(&mut triggers, &update(account, 500, &[], 4)).is_empty());
+        assert!(fired(&mut triggers, &update(account, 60, &[], 6)).is_empty());
+        assert_eq!(
+            fired(&mut triggers, &update(account, 500, &[], 7)),
+            ["above"]
+        );
+    }
+
+    #[test]
+    fn rules_restricted_to_an_account_ignore_others() {
+        let watched = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let mut triggers = AccountTriggers::new(vec![rule(
+            "above",
+            Some(&watched),
+            AccountCondition::LamportsAbove(100),
+        )])
+        .unwrap();
+
+        fired(&mut triggers, &update(other, 0, &[], 1));
+        assert!(fired(&mut triggers, &update(other, 200, &[], 2)).is_empty());
+        fired(&mut triggers, &update(watched, 0, &[], 1));
+        assert_eq!(
+            fired(&mut triggers, &update(watched, 200, &[], 2)),
+            ["above"]
+        );
+
+        let invalid = AccountRule {
+            account: Some("not a pubkey".to_string()),
+            ..rule("invalid", None, AccountCondition::DataChanged)
+        };
+        assert!(AccountTriggers::new(vec![invalid]).is_err());
+    }
+
+    #[test]
+    fn account_event_decodes_an_update() {
+        let pubkey = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let update = SubscribeUpdateAccount {
+            account: Some(crate::geyser::SubscribeUpdateAccountInfo {
+                pubkey: pubkey.to_bytes().to_vec(),
+                lamports: 42,
+                owner: owner.to_bytes().to_vec(),
+                data: vec![1, 2, 3],
+                write_version: 9,
+                ..Default::default()
+            }),
+            slot: 77,
+            is_startup: true,
+        };
+
+        let event = AccountEvent::try_from(update).unwrap();
+        assert_eq!(event.pubkey, pubkey);
+        assert_eq!(event.owner, owner);
+        assert_eq!(event.lamports, 42);
+        assert_eq!(event.data, [1, 2, 3]);
+        assert_eq!((event.slot, event.write_version), (77, 9));
+        assert!(event.is_startup);
+        assert!(event.txn_signature.is_none());
+
+        let empty = SubscribeUpdateAccount {
+            account: None,
+            slot: 0,
+            is_startup: false,
+        };
+        assert!(AccountEvent::try_from(empty).is_err());
+    }
+
+    #[test]
+    fn watch_ignores_accounts_only_the_sweeper_subscribes_to() {
+        let wallet = Pubkey::new_unique();
+        let watched = Pubkey::new_unique();
+        let program = Pubkey::new_unique();
+        let watch = WatchConfig {
+            accounts: vec![watched.to_string()],
+            owners: vec![program.to_string()],
+        };
+
+        assert!(!watch.watches(&event(wallet, solana_sdk::system_program::id())));
+        assert!(watch.watches(&event(watched, solana_sdk::system_program::id())));
+        assert!(watch.watches(&event(Pubkey::new_unique(), program)));
+
+        let watch = WatchConfig {
+            accounts: vec![wallet.to_string()],
+            owners: vec![],
+        };
+        assert!(watch.watches(&event(wallet, solana_sdk::system_program::id())));
+    }
+}
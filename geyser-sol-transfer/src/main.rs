@@ -1,19 +1,16 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use clap::Parser;
 use serde::{Deserialize, Serialize};
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
     message::Message,
-    pubkey::Pubkey,
     signature::{Keypair, Signer},
-    system_instruction,
     transaction::Transaction,
 };
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
-use std::str::FromStr;
 use std::time::Duration;
 use tokio_stream::StreamExt;
 use tonic::{metadata::MetadataValue, transport::Channel, Request};
@@ -22,14 +19,43 @@ pub mod geyser {
     include!(concat!(env!("OUT_DIR"), "/geyser.rs"));
 }
 
+mod accounts;
+mod recipients;
+mod sweeper;
+mod transactions;
+mod triggers;
+
+use accounts::{AccountEvent, AccountRule, AccountTriggers, WatchConfig};
 use geyser::{
-    geyser_client::GeyserClient, SubscribeRequest, SubscribeRequestFilterBlocks, SubscribeUpdate,
+    geyser_client::GeyserClient, subscribe_update::UpdateOneof, SubscribeRequest,
+    SubscribeRequestFilterBlocks,
+};
+use recipients::{
+    pack_transfers, parse_recipients, payouts, transfer_recipients, Recipient, RecipientConfig,
+    Share,
 };
+use sweeper::{Sweeper, SweeperConfig};
+use transactions::{
+    TransactionEvent, TransactionFilterConfig, TransactionRule, TransactionTriggers,
+};
+use triggers::{Action, TriggerEngine, TriggerRule};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 struct Config {
     geyser: GeyserConfig,
     solana: SolanaConfig,
+    #[serde(default)]
+    triggers: Vec<TriggerRule>,
+    #[serde(default)]
+    watch: WatchConfig,
+    #[serde(default)]
+    account_triggers: Vec<AccountRule>,
+    #[serde(default)]
+    watch_transactions: TransactionFilterConfig,
+    #[serde(default)]
+    transaction_triggers: Vec<TransactionRule>,
+    #[serde(default)]
+    sweeper: Option<SweeperConfig>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -42,8 +68,13 @@ struct GeyserConfig {
 struct SolanaConfig {
     rpc_url: String,
     private_key: String,
+    /// Receives every payout in full when `recipients` is empty.
+    #[serde(default)]
     recipient_address: String,
+    /// Lamports paid out by triggers that set no amount of their own.
     transfer_amount: u64,
+    #[serde(default)]
+    recipients: Vec<RecipientConfig>,
 }
 
 #[derive(Parser, Debug)]
@@ -59,7 +90,7 @@ struct SolTransfer {
     config: Config,
     rpc_client: RpcClient,
     keypair: Keypair,
-    recipient_pubkey: Pubkey,
+    recipients: Vec<Recipient>,
     dry_run: bool,
 }
 
@@ -77,58 +108,89 @@ impl SolTransfer {
         let keypair = Keypair::from_bytes(&private_key_bytes)
             .context("Failed to create keypair from private key")?;
 
-        let recipient_pubkey =
-            Pubkey::from_str(&config.solana.recipient_address).context("Err: address recipient")?;
+        let recipients =
+            parse_recipients(&config.solana.recipients, &config.solana.recipient_address)?;
 
-        println!(
-            "Sender: {}, Recipient: {}",
-            keypair.pubkey(),
-            recipient_pubkey
-        );
+        println!("Sender: {}", keypair.pubkey());
+        for recipient in &recipients {
+            match recipient.share {
+                Share::Amount(amount) => {
+                    println!("Recipient: {} {} lamports", recipient.pubkey, amount)
+                }
+                Share::Bps(bps) => println!("Recipient: {} {} bps", recipient.pubkey, bps),
+            }
+        }
 
         Ok(Self {
             config,
             rpc_client,
             keypair,
-            recipient_pubkey,
+            recipients,
             dry_run,
         })
     }
 
-    async fn send_sol_transfer(&self, block_slot: u64) -> Result<()> {
+    /// Messages paying `lamports` out to the recipients.
+    fn payout_messages(&self, lamports: u64) -> Result<Vec<Message>> {
+        pack_transfers(&self.keypair.pubkey(), &payouts(&self.recipients, lamports))
+    }
+
+    /// Pays `lamports` out to the recipients, one transaction per packed
+    /// message. A failed transaction is not resent, as it may still land; the
+    /// remaining messages go out with a fresh blockhash and the recipients
+    /// left unpaid are returned in the error.
+    async fn send_sol_transfer(&self, lamports: u64, block_slot: u64) -> Result<()> {
         if self.dry_run {
-            println!(
-                "DRY RUN: {} lamports -> {} {}",
-                self.config.solana.transfer_amount, self.recipient_pubkey, block_slot
-            );
+            for (recipient, amount) in payouts(&self.recipients, lamports) {
+                println!(
+                    "DRY RUN: {} lamports -> {} {}",
+                    amount, recipient, block_slot
+                );
+            }
             return Ok(());
         }
 
-        let recent_blockhash = self
-            .rpc_client
-            .get_latest_blockhash()
-            .context(":: Failed to receive blockhash")?;
-
-        let instruction = system_instruction::transfer(
-            &self.keypair.pubkey(),
-            &self.recipient_pubkey,
-            self.config.solana.transfer_amount,
-        );
-
-        let message = Message::new(&[instruction], Some(&self.keypair.pubkey()));
-        let mut transaction = Transaction::new_unsigned(message);
-        transaction.sign(&[&self.keypair], recent_blockhash);
-
-        match self.rpc_client.send_and_confirm_transaction(&transaction) {
-            Ok(signature) => {
-                println!("TX: {} {}", signature, block_slot);
-            }
-            Err(e) => {
-                println!("Err: TX: {} {}", e, block_slot);
-                return Err(e.into());
+        let messages = self.payout_messages(lamports)?;
+        let mut recent_blockhash = None;
+        let mut unpaid = Vec::new();
+
+        for message in messages {
+            let recipients = transfer_recipients(&message);
+            let blockhash = match recent_blockhash {
+                Some(blockhash) => blockhash,
+                None => match self.rpc_client.get_latest_blockhash() {
+                    Ok(blockhash) => *recent_blockhash.insert(blockhash),
+                    Err(e) => {
+                        println!("Err: blockhash: {} {}", e, block_slot);
+                        unpaid.extend(recipients);
+                        continue;
+                    }
+                },
+            };
+            let mut transaction = Transaction::new_unsigned(message);
+            transaction.sign(&[&self.keypair], blockhash);
+
+            match self.rpc_client.send_and_confirm_transaction(&transaction) {
+                Ok(signature) => {
+                    println!(
+                        "TX: {} {} ({} transfers)",
+                        signature,
+                        block_slot,
+                        recipients.len()
+                    );
+                }
+                Err(e) => {
+                    println!("Err: TX: {} {}", e, block_slot);
+                    unpaid.extend(recipients);
+                    recent_blockhash = None;
+                }
             }
         }
 
+        if !unpaid.is_empty() {
+            let unpaid: Vec<_> = unpaid.iter().map(ToString::to_string).collect();
+            bail!("Err: unpaid recipients: {}", unpaid.join(", "));
+        }
         Ok(())
     }
 }
@@ -146,17 +208,55 @@ async fn setup_geyser_connection(config: &GeyserConfig) -> Result<GeyserClient<C
     Ok(client)
 }
 
-async fn subscribe_to_blocks(
+/// The rule engines of every update type and the sweeper, kept across reconnects.
+struct Triggers {
+    blocks: TriggerEngine,
+    accounts: AccountTriggers,
+    transactions: TransactionTriggers,
+    sweeper: Option<Sweeper>,
+}
+
+async fn run_action(sol_transfer: &SolTransfer, name: &str, action: &Action, slot: u64) {
+    println!("Trigger {} fired at {}", name, slot);
+    if let Action::Transfer { amount } = action {
+        let lamports = amount.unwrap_or(sol_transfer.config.solana.transfer_amount);
+        if let Err(e) = sol_transfer.send_sol_transfer(lamports, slot).await {
+            println!("Err: send {}: {}", name, e);
+        }
+    }
+}
+
+async fn subscribe(
     sol_transfer: &SolTransfer,
+    triggers: &mut Triggers,
     mut client: GeyserClient<Channel>,
 ) -> Result<()> {
+    let watch = &sol_transfer.config.watch;
+    let watch_transactions = &sol_transfer.config.watch_transactions;
+
+    // Blocks are only streamed when something uses them or nothing else is watched.
     let mut blocks_filter = HashMap::new();
-    blocks_filter.insert("client".to_string(), SubscribeRequestFilterBlocks {});
+    if !triggers.blocks.is_empty()
+        || (watch.is_empty() && watch_transactions.is_empty() && triggers.sweeper.is_none())
+    {
+        blocks_filter.insert("client".to_string(), SubscribeRequestFilterBlocks {});
+    }
+    let mut accounts_filter = HashMap::new();
+    if !watch.is_empty() {
+        accounts_filter.insert("client".to_string(), watch.filter());
+    }
+    if let Some(sweeper) = &triggers.sweeper {
+        accounts_filter.insert("sweeper".to_string(), sweeper.filter());
+    }
+    let mut transactions_filter = HashMap::new();
+    if !watch_transactions.is_empty() {
+        transactions_filter.insert("client".to_string(), watch_transactions.filter());
+    }
 
     let subscribe_request = SubscribeRequest {
         slots: HashMap::new(),
-        accounts: HashMap::new(),
-        transactions: HashMap::new(),
+        accounts: accounts_filter,
+        transactions: transactions_filter,
         blocks: blocks_filter,
         blocks_meta: HashMap::new(),
         accounts_data_slice: vec![],
@@ -181,30 +281,86 @@ async fn subscribe_to_blocks(
         .into_inner();
 
     while let Some(update) = stream.next().await {
-        match update {
-            Ok(subscribe_update) => {
-                if let Some(update_oneof) = subscribe_update.update_oneof {
-                    match update_oneof {
-                        geyser::subscribe_update::UpdateOneof::Block(block) => {
-                            println!(
-                                "Block {} {}, TX: {})",
-                                block.slot, block.block_height, block.executed_transaction_count
-                            );
-
-                            if let Err(e) = sol_transfer.send_sol_transfer(block.slot).await {
-                                println!("Err: send: {}", e);
-                            }
-
-                            tokio::time::sleep(Duration::from_millis(100)).await;
-                        }
-                        _ => {}
-                    }
-                }
-            }
+        let update_oneof = match update {
+            Ok(subscribe_update) => subscribe_update.update_oneof,
             Err(e) => {
                 println!("Err stream: {}", e);
                 break;
             }
+        };
+
+        match update_oneof {
+            Some(UpdateOneof::Block(block)) => {
+                println!(
+                    "Block {} {}, TX: {})",
+                    block.slot, block.block_height, block.executed_transaction_count
+                );
+
+                let fired = triggers.blocks.evaluate(&block);
+                if fired.is_empty() {
+                    continue;
+                }
+                for rule in fired {
+                    run_action(sol_transfer, &rule.name, &rule.action, block.slot).await;
+                }
+
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            Some(UpdateOneof::Account(update)) => {
+                let event = match AccountEvent::try_from(update) {
+                    Ok(event) => event,
+                    Err(e) => {
+                        println!("Err: account update: {}", e);
+                        continue;
+                    }
+                };
+                println!(
+                    "Account {} of {}: {} lamports, {} bytes at {}{}",
+                    event.pubkey,
+                    event.owner,
+                    event.lamports,
+                    event.data.len(),
+                    event.slot,
+                    match (event.is_startup, event.txn_signature) {
+                        (true, _) => " (startup)".to_string(),
+                        (false, Some(signature)) => format!(", TX: {}", signature),
+                        (false, None) => String::new(),
+                    }
+                );
+
+                // The sweeper's wallet only reaches account rules when watched too.
+                if watch.watches(&event) {
+                    for rule in triggers.accounts.evaluate(&event) {
+                        run_action(sol_transfer, &rule.name, &rule.action, event.slot).await;
+                    }
+                }
+                if let Some(sweeper) = &mut triggers.sweeper {
+                    if let Err(e) = sweeper.on_update(sol_transfer, &event).await {
+                        println!("Err: sweep: {}", e);
+                    }
+                }
+            }
+            Some(UpdateOneof::Transaction(update)) => {
+                let event = match TransactionEvent::try_from(update) {
+                    Ok(event) => event,
+                    Err(e) => {
+                        println!("Err: transaction update: {}", e);
+                        continue;
+                    }
+                };
+                println!(
+                    "Transaction {} at {}, fee {}{}",
+                    event.signature,
+                    event.slot,
+                    event.fee,
+                    if event.failed { " (failed)" } else { "" }
+                );
+
+                for rule in triggers.transactions.evaluate(&event) {
+                    run_action(sol_transfer, &rule.name, &rule.action, event.slot).await;
+                }
+            }
+            _ => {}
         }
     }
 
@@ -226,13 +382,36 @@ async fn main() -> Result<()> {
     let config = load_config(&args.config)?;
 
     let sol_transfer = SolTransfer::new(config.clone(), args.dry_run)?;
+    let mut triggers = Triggers {
+        blocks: TriggerEngine::new(config.triggers.clone()),
+        accounts: AccountTriggers::new(config.account_triggers.clone())?,
+        transactions: TransactionTriggers::new(config.transaction_triggers.clone()),
+        sweeper: config
+            .sweeper
+            .clone()
+            .map(|sweeper| Sweeper::new(sweeper, &sol_transfer))
+            .transpose()?,
+    };
+    if triggers.blocks.is_empty()
+        && triggers.accounts.is_empty()
+        && triggers.transactions.is_empty()
+        && triggers.sweeper.is_none()
+    {
+        println!("No triggers configured, updates are only logged");
+    }
+    if !triggers.accounts.is_empty() && config.watch.is_empty() {
+        println!("Account triggers configured without watched accounts");
+    }
+    if !triggers.transactions.is_empty() && config.watch_transactions.is_empty() {
+        println!("Transaction triggers configured without watched transactions");
+    }
 
     loop {
         match setup_geyser_connection(&config.geyser).await {
             Ok(client) => {
                 println!("Connect geyser");
 
-                if let Err(e) = subscribe_to_blocks(&sol_transfer, client).await {
+                if let Err(e) = subscribe(&sol_transfer, &mut triggers, client).await {
                     println!("Err: subscription: {}", e);
                 }
             }
@@ -242,3 +421,25 @@ async fn main() -> Result<()> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_config_moves_no_funds() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("config.yaml");
+        let config = load_config(&path).unwrap();
+        let actions = config
+            .triggers
+            .iter()
+            .map(|rule| &rule.action)
+            .chain(config.account_triggers.iter().map(|rule| &rule.action))
+            .chain(config.transaction_triggers.iter().map(|rule| &rule.action));
+        for action in actions {
+            assert_eq!(*action, Action::Log);
+        }
+        assert!(config.solana.recipients.is_empty());
+        assert!(config.sweeper.is_none());
+    }
+}
@@ -0,0 +1,294 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    instruction::Instruction, message::Message, packet::PACKET_DATA_SIZE, pubkey::Pubkey,
+    system_instruction, transaction::Transaction,
+};
+use std::str::FromStr;
+
+/// One entry of `solana.recipients`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecipientConfig {
+    pub address: String,
+    #[serde(flatten)]
+    pub share: Share,
+}
+
+/// What a recipient gets out of each payout.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Share {
+    /// A fixed number of lamports, whatever the payout.
+    Amount(u64),
+    /// Basis points (hundredths of a percent) of the payout, rounded down.
+    Bps(u16),
+}
+
+/// Basis points of a whole payout.
+pub const MAX_BPS: u16 = 10_000;
+
+#[derive(Debug, Clone)]
+pub struct Recipient {
+    pub pubkey: Pubkey,
+    pub share: Share,
+}
+
+/// Parses the recipient list, falling back to `recipient_address` receiving
+/// the whole payout when the list is empty.
+pub fn parse_recipients(
+    recipients: &[RecipientConfig],
+    recipient_address: &str,
+) -> Result<Vec<Recipient>> {
+    if recipients.is_empty() {
+        let pubkey = Pubkey::from_str(recipient_address).context("Err: address recipient")?;
+        return Ok(vec![Recipient {
+            pubkey,
+            share: Share::Bps(MAX_BPS),
+        }]);
+    }
+    if !recipient_address.is_empty() {
+        bail!("Err: set either recipient_address or recipients");
+    }
+
+    let mut bps = 0u32;
+    let recipients = recipients
+        .iter()
+        .map(|recipient| {
+            if let Share::Bps(share) = recipient.share {
+                bps += share as u32;
+            }
+            Ok(Recipient {
+                pubkey: Pubkey::from_str(&recipient.address)
+                    .with_context(|| format!("Err: address recipient {}", recipient.address))?,
+                share: recipient.share,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if bps > MAX_BPS as u32 {
+        bail!("Err: recipient bps add up to {}", bps);
+    }
+    Ok(recipients)
+}
+
+/// Splits a payout of `lamports` between `recipients`, leaving out zero transfers.
+pub fn payouts(recipients: &[Recipient], lamports: u64) -> Vec<(Pubkey, u64)> {
+    recipients
+        .iter()
+        .map(|recipient| {
+            let amount = match recipient.share {
+                Share::Amount(amount) => amount,
+                Share::Bps(bps) => (lamports as u128 * bps as u128 / MAX_BPS as u128) as u64,
+            };
+            (recipient.pubkey, amount)
+        })
+        .filter(|(_, amount)| *amount > 0)
+        .collect()
+}
+
+/// Packs transfers from `payer` into as few messages as fit in a transaction.
+pub fn pack_transfers(payer: &Pubkey, payouts: &[(Pubkey, u64)]) -> Result<Vec<Message>> {
+    let transfers = payouts
+        .iter()
+        .map(|(recipient, lamports)| system_instruction::transfer(payer, recipient, *lamports));
+    pack_instructions(payer, transfers)
+}
+
+/// Recipients of the transfers in a message built by [`pack_transfers`].
+pub fn transfer_recipients(message: &Message) -> Vec<Pubkey> {
+    message
+        .instructions
+        .iter()
+        .filter_map(|instruction| instruction.accounts.get(1))
+        .filter_map(|index| message.account_keys.get(*index as usize))
+        .copied()
+        .collect()
+}
+
+/// Greedily fills each message with `instructions` up to the transaction size limit.
+fn pack_instructions(
+    payer: &Pubkey,
+    instructions: impl IntoIterator<Item = Instruction>,
+) -> Result<Vec<Message>> {
+    let mut messages = Vec::new();
+    let mut batch: Vec<Instruction> = Vec::new();
+
+    for instruction in instructions {
+        let program_id = instruction.program_id;
+        batch.push(instruction);
+        if transaction_size(payer, &batch)? <= PACKET_DATA_SIZE {
+            continue;
+        }
+
+        // Start a new message with the instruction that overflowed.
+        let overflow = batch.split_off(batch.len() - 1);
+        if !batch.is_empty() {
+            messages.push(Message::new(&batch, Some(payer)));
+        }
+        batch = overflow;
+        if transaction_size(payer, &batch)? > PACKET_DATA_SIZE {
+            bail!(
+                "Err: instruction of {} does not fit a transaction",
+                program_id
+            );
+        }
+    }
+    if !batch.is_empty() {
+        messages.push(Message::new(&batch, Some(payer)));
+    }
+    Ok(messages)
+}
+
+/// Size of the signed transaction carrying `instructions`.
+fn transaction_size(payer: &Pubkey, instructions: &[Instruction]) -> Result<usize> {
+    let transaction = Transaction::new_unsigned(Message::new(instructions, Some(payer)));
+    let size = bincode::serialized_size(&transaction).context("Err: transaction size")?;
+    Ok(size as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(address: &Pubkey, share: Share) -> RecipientConfig {
+        RecipientConfig {
+            address: address.to_string(),
+            share,
+        }
+    }
+
+    #[test]
+    fn recipient_address_alone_gets_the_whole_payout() {
+        let address = Pubkey::new_unique();
+        let recipients = parse_recipients(&[], &address.to_string()).unwrap();
+
+        assert_eq!(recipients.len(), 1);
+        assert_eq!(recipients[0].pubkey, address);
+        assert_eq!(payouts(&recipients, 12_345), vec![(address, 12_345)]);
+    }
+
+    #[test]
+    fn recipients_parse_from_yaml() {
+        let yaml = r#"
+- address: "11111111111111111111111111111111"
+  amount: 250000
+- address: "11111111111111111111111111111111"
+  bps: 6000
+"#;
+        let configs: Vec<RecipientConfig> = serde_yaml::from_str(yaml).unwrap();
+        let recipients = parse_recipients(&configs, "").unwrap();
+
+        assert!(matches!(recipients[0].share, Share::Amount(250_000)));
+        assert!(matches!(recipients[1].share, Share::Bps(6_000)));
+    }
+
+    #[test]
+    fn parse_recipients_rejects_bad_lists() {
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+
+        let full = [config(&a, Share::Bps(6_000)), config(&b, Share::Bps(4_000))];
+        assert!(parse_recipients(&full, "").is_ok());
+
+        let over = [config(&a, Share::Bps(6_000)), config(&b, Share::Bps(4_001))];
+        assert!(parse_recipients(&over, "").is_err());
+
+        assert!(parse_recipients(&full, &a.to_string()).is_err());
+        assert!(parse_recipients(&[], "").is_err());
+
+        let invalid = [RecipientConfig {
+            address: "not a pubkey".to_string(),
+            share: Share::Amount(1),
+        }];
+        assert!(parse_recipients(&invalid, "").is_err());
+    }
+
+    #[test]
+    fn payouts_round_basis_points_down_and_skip_zeros() {
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        let c = Pubkey::new_unique();
+        let recipients = [
+            Recipient {
+                pubkey: a,
+                share: Share::Amount(100),
+            },
+            Recipient {
+                pubkey: b,
+                share: Share::Bps(3_333),
+            },
+            Recipient {
+                pubkey: c,
+                share: Share::Bps(1),
+            },
+        ];
+
+        assert_eq!(payouts(&recipients, 1_000), vec![(a, 100), (b, 333)]);
+        assert_eq!(
+            payouts(&recipients, 10_000),
+            vec![(a, 100), (b, 3_333), (c, 1)]
+        );
+    }
+
+    #[test]
+    fn payouts_do_not_overflow_on_large_amounts() {
+        let pubkey = Pubkey::new_unique();
+        let recipients = [Recipient {
+            pubkey,
+            share: Share::Bps(MAX_BPS),
+        }];
+
+        assert_eq!(payouts(&recipients, u64::MAX), vec![(pubkey, u64::MAX)]);
+    }
+
+    #[test]
+    fn pack_transfers_splits_at_the_packet_size() {
+        let payer = Pubkey::new_unique();
+        let payouts: Vec<_> = (1..=60).map(|i| (Pubkey::new_unique(), i)).collect();
+        let messages = pack_transfers(&payer, &payouts).unwrap();
+        assert!(messages.len() > 1);
+
+        let transfers: Vec<_> = payouts
+            .iter()
+            .map(|(recipient, lamports)| system_instruction::transfer(&payer, recipient, *lamports))
+            .collect();
+        let mut start = 0;
+        for (i, message) in messages.iter().enumerate() {
+            let end = start + message.instructions.len();
+            assert!(transaction_size(&payer, &transfers[start..end]).unwrap() <= PACKET_DATA_SIZE);
+            // Every message but the last is full.
+            if i + 1 < messages.len() {
+                assert!(
+                    transaction_size(&payer, &transfers[start..=end]).unwrap() > PACKET_DATA_SIZE
+                );
+            }
+            start = end;
+        }
+
+        let recipients: Vec<_> = messages.iter().flat_map(transfer_recipients).collect();
+        let expected: Vec<_> = payouts.iter().map(|(recipient, _)| *recipient).collect();
+        assert_eq!(recipients, expected);
+    }
+
+    #[test]
+    fn pack_transfers_fits_few_transfers_in_one_message() {
+        let payer = Pubkey::new_unique();
+        let payouts = [(Pubkey::new_unique(), 1), (Pubkey::new_unique(), 2)];
+
+        let messages = pack_transfers(&payer, &payouts).unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].instructions.len(), 2);
+        assert!(pack_transfers(&payer, &[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn pack_rejects_an_instruction_too_large_for_a_transaction() {
+        let payer = Pubkey::new_unique();
+        let large =
+            Instruction::new_with_bytes(Pubkey::new_unique(), &[0; PACKET_DATA_SIZE], vec![]);
+        let transfer = system_instruction::transfer(&payer, &Pubkey::new_unique(), 1);
+
+        assert!(pack_instructions(&payer, [large.clone()]).is_err());
+        assert!(pack_instructions(&payer, [transfer.clone(), large.clone()]).is_err());
+        assert!(pack_instructions(&payer, [large, transfer]).is_err());
+    }
+}
@@ -0,0 +1,277 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+use crate::accounts::AccountEvent;
+use crate::geyser::SubscribeRequestFilterAccounts;
+use crate::recipients::{payouts, Recipient};
+use crate::SolTransfer;
+
+/// Sweeps the sender wallet on start and whenever its balance grows, splitting
+/// each sweep between the configured recipients like any other payout.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SweeperConfig {
+    /// Lamports always left in the wallet, raised to the rent-exempt minimum.
+    #[serde(default)]
+    pub reserve: u64,
+    /// Smaller sweeps are skipped so dust does not cost a fee each time.
+    #[serde(default)]
+    pub min_sweep: u64,
+}
+
+pub struct Sweeper {
+    config: SweeperConfig,
+    wallet: Pubkey,
+    keep: u64,
+    last: Option<Seen>,
+}
+
+/// The newest wallet update handled.
+struct Seen {
+    write_version: u64,
+    lamports: u64,
+}
+
+/// What to do with the wallet balance once the payout fees are known.
+#[derive(Debug, PartialEq, Eq)]
+enum Plan {
+    /// Pay out this many lamports.
+    Sweep(u64),
+    /// Less than `min_sweep` is left above the reserve and fees.
+    TooSmall(u64),
+    /// Fixed payouts of `paid` lamports exceed the `amount` sweepable.
+    Overpaid { paid: u64, amount: u64 },
+}
+
+impl Sweeper {
+    pub fn new(config: SweeperConfig, sol_transfer: &SolTransfer) -> Result<Self> {
+        let rent_exempt = sol_transfer
+            .rpc_client
+            .get_minimum_balance_for_rent_exemption(0)
+            .context("Err: rent exemption")?;
+        let sweeper = Self::with_rent(config, sol_transfer.keypair.pubkey(), rent_exempt);
+        println!(
+            "Sweeping {} above {} lamports",
+            sweeper.wallet, sweeper.keep
+        );
+        Ok(sweeper)
+    }
+
+    fn with_rent(config: SweeperConfig, wallet: Pubkey, rent_exempt: u64) -> Self {
+        Self {
+            keep: config.reserve.max(rent_exempt),
+            config,
+            wallet,
+            last: None,
+        }
+    }
+
+    pub fn filter(&self) -> SubscribeRequestFilterAccounts {
+        SubscribeRequestFilterAccounts {
+            account: vec![self.wallet.to_string()],
+            owner: vec![],
+            filters: false,
+        }
+    }
+
+    /// Sweeps the wallet when `event` is a new increase of its balance, or the
+    /// first update seen with funds above the reserve.
+    ///
+    /// Replayed or out of order updates are dropped by write version, and the
+    /// amount comes from the live balance, so a burst of deposits is swept by
+    /// the first update and the updates after it find nothing left.
+    pub async fn on_update(
+        &mut self,
+        sol_transfer: &SolTransfer,
+        event: &AccountEvent,
+    ) -> Result<()> {
+        if !self.observe(event) {
+            return Ok(());
+        }
+
+        let balance = sol_transfer
+            .rpc_client
+            .get_balance(&self.wallet)
+            .context("Err: wallet balance")?;
+        let mut fee = 0;
+        for message in sol_transfer.payout_messages(balance)? {
+            fee += sol_transfer
+                .rpc_client
+                .get_fee_for_message(&message)
+                .context("Err: sweep fee")?;
+        }
+
+        match self.plan(&sol_transfer.recipients, balance, fee) {
+            Plan::Sweep(amount) => {
+                println!("Sweep {} of {} lamports", amount, balance);
+                sol_transfer.send_sol_transfer(amount, event.slot).await
+            }
+            Plan::TooSmall(amount) => {
+                println!("Sweep skipped: {} lamports sweepable", amount);
+                Ok(())
+            }
+            Plan::Overpaid { paid, amount } => {
+                println!(
+                    "Sweep skipped: payouts of {} exceed {} sweepable",
+                    paid, amount
+                );
+                Ok(())
+            }
+        }
+    }
+
+    /// Records `event` and returns whether it calls for a sweep: a new update
+    /// of the wallet that raised its balance, or the first one seen while the
+    /// balance is already above what is kept.
+    fn observe(&mut self, event: &AccountEvent) -> bool {
+        if event.pubkey != self.wallet {
+            return false;
+        }
+        if let Some(last) = &self.last {
+            if event.write_version <= last.write_version {
+                return false;
+            }
+        }
+        let previous = self.last.replace(Seen {
+            write_version: event.write_version,
+            lamports: event.lamports,
+        });
+        match previous {
+            Some(previous) => event.lamports > previous.lamports,
+            None => event.lamports > self.keep,
+        }
+    }
+
+    /// Decides how much of `balance` to pay out to `recipients` once `fee`
+    /// lamports of transaction fees are set aside.
+    fn plan(&self, recipients: &[Recipient], balance: u64, fee: u64) -> Plan {
+        let amount = balance.saturating_sub(self.keep).saturating_sub(fee);
+        if amount == 0 || amount < self.config.min_sweep {
+            return Plan::TooSmall(amount);
+        }
+        // Fixed amounts do not scale down with the sweep.
+        let paid: u64 = payouts(recipients, amount)
+            .iter()
+            .map(|(_, lamports)| lamports)
+            .sum();
+        if paid > amount {
+            return Plan::Overpaid { paid, amount };
+        }
+        Plan::Sweep(amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recipients::Share;
+
+    const RENT: u64 = 890_880;
+
+    fn sweeper(reserve: u64, min_sweep: u64) -> Sweeper {
+        let config = SweeperConfig { reserve, min_sweep };
+        Sweeper::with_rent(config, Pubkey::new_unique(), RENT)
+    }
+
+    fn update(sweeper: &Sweeper, lamports: u64, write_version: u64) -> AccountEvent {
+        AccountEvent {
+            pubkey: sweeper.wallet,
+            owner: Pubkey::default(),
+            lamports,
+            data: vec![],
+            slot: 0,
+            write_version,
+            txn_signature: None,
+            is_startup: false,
+        }
+    }
+
+    fn recipient(share: Share) -> Recipient {
+        Recipient {
+            pubkey: Pubkey::new_unique(),
+            share,
+        }
+    }
+
+    #[test]
+    fn keeps_at_least_the_rent_exempt_minimum() {
+        assert_eq!(sweeper(0, 0).keep, RENT);
+        assert_eq!(sweeper(RENT - 1, 0).keep, RENT);
+        assert_eq!(sweeper(5_000_000, 0).keep, 5_000_000);
+    }
+
+    #[test]
+    fn first_update_sweeps_only_above_keep() {
+        let mut low = sweeper(1_000, 0);
+        assert!(!low.observe(&update(&low, RENT, 1)));
+        assert!(low.observe(&update(&low, RENT + 1, 2)));
+
+        let mut high = sweeper(1_000, 0);
+        assert!(high.observe(&update(&high, RENT + 1, 1)));
+    }
+
+    #[test]
+    fn later_updates_sweep_only_on_increase() {
+        let mut sweeper = sweeper(0, 0);
+        sweeper.observe(&update(&sweeper, 5 * RENT, 1));
+        assert!(!sweeper.observe(&update(&sweeper, 5 * RENT, 2)));
+        assert!(!sweeper.observe(&update(&sweeper, RENT, 3)));
+        assert!(sweeper.observe(&update(&sweeper, RENT + 1, 4)));
+    }
+
+    #[test]
+    fn replayed_and_out_of_order_updates_are_dropped() {
+        let mut sweeper = sweeper(0, 0);
+        sweeper.observe(&update(&sweeper, RENT, 5));
+        assert!(!sweeper.observe(&update(&sweeper, 2 * RENT, 5)));
+        assert!(!sweeper.observe(&update(&sweeper, 3 * RENT, 4)));
+        assert_eq!(sweeper.last.as_ref().unwrap().write_version, 5);
+        assert!(sweeper.observe(&update(&sweeper, 2 * RENT, 6)));
+    }
+
+    #[test]
+    fn other_accounts_are_ignored() {
+        let mut sweeper = sweeper(0, 0);
+        let mut event = update(&sweeper, 2 * RENT, 1);
+        event.pubkey = Pubkey::new_unique();
+        assert!(!sweeper.observe(&event));
+        assert!(sweeper.last.is_none());
+    }
+
+    #[test]
+    fn plan_leaves_keep_and_fees() {
+        let sweeper = sweeper(1_000_000, 0);
+        let all = [recipient(Share::Bps(10_000))];
+        assert_eq!(sweeper.plan(&all, 3_000_000, 5_000), Plan::Sweep(1_995_000));
+        assert_eq!(sweeper.plan(&all, 1_000_000, 5_000), Plan::TooSmall(0));
+        assert_eq!(sweeper.plan(&all, 1_004_000, 5_000), Plan::TooSmall(0));
+    }
+
+    #[test]
+    fn plan_skips_sweeps_below_min_sweep() {
+        let sweeper = sweeper(1_000_000, 500_000);
+        let all = [recipient(Share::Bps(10_000))];
+        assert_eq!(sweeper.plan(&all, 1_499_999, 0), Plan::TooSmall(499_999));
+        assert_eq!(sweeper.plan(&all, 1_500_000, 0), Plan::Sweep(500_000));
+    }
+
+    #[test]
+    fn plan_skips_fixed_payouts_over_the_sweep() {
+        let sweeper = sweeper(1_000_000, 0);
+        let recipients = [
+            recipient(Share::Amount(400_000)),
+            recipient(Share::Bps(5_000)),
+        ];
+        assert_eq!(
+            sweeper.plan(&recipients, 1_600_000, 0),
+            Plan::Overpaid {
+                paid: 700_000,
+                amount: 600_000
+            }
+        );
+        assert_eq!(
+            sweeper.plan(&recipients, 1_800_000, 0),
+            Plan::Sweep(800_000)
+        );
+    }
+}
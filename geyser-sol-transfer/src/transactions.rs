@@ -0,0 +1,426 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{pubkey::Pubkey, signature::Signature, transaction::VersionedTransaction};
+
+use crate::geyser::{SubscribeRequestFilterTransactions, SubscribeUpdateTransaction};
+use crate::triggers::Action;
+
+/// Transactions to subscribe to, by the accounts they reference.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TransactionFilterConfig {
+    /// Transactions touching any of these accounts.
+    #[serde(default)]
+    pub account_include: Vec<String>,
+    /// Transactions touching none of these accounts.
+    #[serde(default)]
+    pub account_exclude: Vec<String>,
+    /// Transactions touching all of these accounts.
+    #[serde(default)]
+    pub account_required: Vec<String>,
+}
+
+impl TransactionFilterConfig {
+    /// An exclude-only filter would stream every transaction, so it counts as empty.
+    pub fn is_empty(&self) -> bool {
+        self.account_include.is_empty() && self.account_required.is_empty()
+    }
+
+    pub fn filter(&self) -> SubscribeRequestFilterTransactions {
+        SubscribeRequestFilterTransactions {
+            account: vec![],
+            account_include: self.account_include.clone(),
+            account_exclude: self.account_exclude.clone(),
+            account_required: self.account_required.clone(),
+        }
+    }
+}
+
+/// A named rule that fires when a transaction meets all of its conditions.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TransactionRule {
+    pub name: String,
+    #[serde(with = "serde_yaml::with::singleton_map_recursive")]
+    pub conditions: Vec<TransactionCondition>,
+    #[serde(default, with = "serde_yaml::with::singleton_map")]
+    pub action: Action,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionCondition {
+    /// `account` gains at least `min` lamports.
+    BalanceIncrease {
+        #[serde(with = "pubkey_string")]
+        account: Pubkey,
+        #[serde(default)]
+        min: u64,
+    },
+    /// `account` loses at least `min` lamports, fees included.
+    BalanceDecrease {
+        #[serde(with = "pubkey_string")]
+        account: Pubkey,
+        #[serde(default)]
+        min: u64,
+    },
+    /// The fee paid is at least this many lamports.
+    MinFee(u64),
+    Succeeded,
+    Failed,
+    /// A log message contains this text.
+    LogContains(String),
+}
+
+/// A `SubscribeUpdateTransaction` with its raw fields decoded.
+#[derive(Debug, Clone)]
+pub struct TransactionEvent {
+    pub signature: Signature,
+    pub slot: u64,
+    pub is_vote: bool,
+    pub failed: bool,
+    pub fee: u64,
+    /// Static keys followed by writable and readonly lookup table addresses,
+    /// in the order the balances use. Empty when the update omits the message.
+    pub account_keys: Vec<Pubkey>,
+    pub pre_balances: Vec<u64>,
+    pub post_balances: Vec<u64>,
+    pub log_messages: Vec<String>,
+}
+
+impl TryFrom<SubscribeUpdateTransaction> for TransactionEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(update: SubscribeUpdateTransaction) -> Result<Self> {
+        let info = update
+            .transaction
+            .ok_or_else(|| anyhow!("Err: transaction update without transaction"))?;
+        let meta = info
+            .meta
+            .ok_or_else(|| anyhow!("Err: transaction update without meta"))?;
+
+        let mut account_keys = Vec::new();
+        if !info.transaction.is_empty() {
+            let transaction: VersionedTransaction =
+                bincode::deserialize(&info.transaction).context("Err: transaction message")?;
+            account_keys.extend_from_slice(transaction.message.static_account_keys());
+            for address in meta
+                .loaded_writable_addresses
+                .iter()
+                .chain(&meta.loaded_readonly_addresses)
+            {
+                account_keys
+                    .push(Pubkey::try_from(address.as_slice()).context("Err: loaded address")?);
+            }
+        }
+
+        Ok(Self {
+            signature: Signature::try_from(info.signature.as_slice())
+                .context("Err: transaction signature")?,
+            slot: update.slot,
+            is_vote: info.is_vote,
+            failed: meta.err != 0,
+            fee: meta.fee,
+            account_keys,
+            pre_balances: meta.pre_balances,
+            post_balances: meta.post_balances,
+            log_messages: meta.log_messages,
+        })
+    }
+}
+
+impl TransactionEvent {
+    /// Lamports `account` gained (positive) or lost (negative), if it took part.
+    pub fn balance_delta(&self, account: &Pubkey) -> Option<i128> {
+        let index = self.account_keys.iter().position(|key| key == account)?;
+        let pre = *self.pre_balances.get(index)?;
+        let post = *self.post_balances.get(index)?;
+        Some(post as i128 - pre as i128)
+    }
+}
+
+/// Evaluates transaction rules.
+pub struct TransactionTriggers {
+    rules: Vec<TransactionRule>,
+}
+
+impl TransactionTriggers {
+    pub fn new(rules: Vec<TransactionRule>) -> Self {
+        Self { rules }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Returns the rules `event` fires, in config order. Vote transactions
+    /// never fire.
+    pub fn evaluate(&self, event: &TransactionEvent) -> Vec<&TransactionRule> {
+        if event.is_vote {
+            return Vec::new();
+        }
+        self.rules
+            .iter()
+            .filter(|rule| {
+                rule.conditions
+                    .iter()
+                    .all(|condition| condition.matches(event))
+            })
+            .collect()
+    }
+}
+
+impl TransactionCondition {
+    fn matches(&self, event: &TransactionEvent) -> bool {
+        match self {
+            TransactionCondition::BalanceIncrease { account, min } => event
+                .balance_delta(account)
+                .is_some_and(|delta| delta > 0 && delta >= *min as i128),
+            TransactionCondition::BalanceDecrease { account, min } => event
+                .balance_delta(account)
+                .is_some_and(|delta| delta < 0 && -delta >= *min as i128),
+            TransactionCondition::MinFee(fee) => event.fee >= *fee,
+            TransactionCondition::Succeeded => !event.failed,
+            TransactionCondition::Failed => event.failed,
+            TransactionCondition::LogContains(text) => event
+                .log_messages
+                .iter()
+                .any(|line| line.contains(text.as_str())),
+        }
+    }
+}
+
+/// Reads and writes pubkeys as base58 strings in the config.
+mod pubkey_string {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use solana_sdk::pubkey::Pubkey;
+    use std::str::FromStr;
+
+    pub fn serialize<S: Serializer>(pubkey: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(pubkey)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Pubkey::from_str(&value).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geyser::{SubscribeUpdateTransactionInfo, SubscribeUpdateTransactionInfoMeta};
+    use solana_sdk::{
+        hash::Hash,
+        message::{v0, MessageHeader, VersionedMessage},
+        signature::Signature,
+    };
+
+    struct Keys {
+        payer: Pubkey,
+        recipient: Pubkey,
+        loaded_writable: Pubkey,
+        loaded_readonly: Pubkey,
+    }
+
+    fn keys() -> Keys {
+        Keys {
+            payer: Pubkey::new_unique(),
+            recipient: Pubkey::new_unique(),
+            loaded_writable: Pubkey::new_unique(),
+            loaded_readonly: Pubkey::new_unique(),
+        }
+    }
+
+    /// A v0 transaction with two static keys and two lookup table addresses,
+    /// its balances in key order.
+    fn update(keys: &Keys, balances: [(u64, u64); 4], err: i32) -> SubscribeUpdateTransaction {
+        let message = v0::Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 0,
+            },
+            account_keys: vec![keys.payer, keys.recipient],
+            recent_blockhash: Hash::default(),
+            instructions: vec![],
+            address_table_lookups: vec![v0::MessageAddressTableLookup {
+                account_key: Pubkey::new_unique(),
+                writable_indexes: vec![0],
+                readonly_indexes: vec![1],
+            }],
+        };
+        let transaction = VersionedTransaction {
+            signatures: vec![Signature::new_unique()],
+            message: VersionedMessage::V0(message),
+        };
+
+        SubscribeUpdateTransaction {
+            transaction: Some(SubscribeUpdateTransactionInfo {
+                signature: transaction.signatures[0].as_ref().to_vec(),
+                is_vote: false,
+                meta: Some(SubscribeUpdateTransactionInfoMeta {
+                    err,
+                    fee: 5_000,
+                    pre_balances: balances.iter().map(|(pre, _)| *pre).collect(),
+                    post_balances: balances.iter().map(|(_, post)| *post).collect(),
+                    log_messages: vec!["Program log: slippage exceeded".to_string()],
+                    loaded_writable_addresses: vec![keys.loaded_writable.to_bytes().to_vec()],
+                    loaded_readonly_addresses: vec![keys.loaded_readonly.to_bytes().to_vec()],
+                    ..Default::default()
+                }),
+                transaction: bincode::serialize(&transaction).unwrap(),
+            }),
+            slot: 10,
+        }
+    }
+
+    fn event(keys: &Keys, balances: [(u64, u64); 4], err: i32) -> TransactionEvent {
+        TransactionEvent::try_from(update(keys, balances, err)).unwrap()
+    }
+
+    fn fired(conditions: Vec<TransactionCondition>, event: &TransactionEvent) -> bool {
+        let triggers = TransactionTriggers::new(vec![TransactionRule {
+            name: "rule".to_string(),
+            conditions,
+            action: Action::Log,
+        }]);
+        !triggers.evaluate(event).is_empty()
+    }
+
+    const BALANCES: [(u64, u64); 4] = [(1_000_000, 394_999), (0, 600_000), (50, 80), (7, 7)];
+
+    #[test]
+    fn decodes_a_versioned_transaction_with_loaded_addresses() {
+        let keys = keys();
+        let update = update(&keys, BALANCES, 0);
+        let signature = update.transaction.as_ref().unwrap().signature.clone();
+        let event = TransactionEvent::try_from(update).unwrap();
+
+        assert_eq!(event.signature.as_ref(), signature.as_slice());
+        assert_eq!(event.slot, 10);
+        assert_eq!(event.fee, 5_000);
+        assert!(!event.failed);
+        assert_eq!(
+            event.account_keys,
+            [
+                keys.payer,
+                keys.recipient,
+                keys.loaded_writable,
+                keys.loaded_readonly
+            ]
+        );
+        assert_eq!(event.balance_delta(&keys.payer), Some(-605_001));
+        assert_eq!(event.balance_delta(&keys.recipient), Some(600_000));
+        assert_eq!(event.balance_delta(&keys.loaded_writable), Some(30));
+        assert_eq!(event.balance_delta(&keys.loaded_readonly), Some(0));
+        assert_eq!(event.balance_delta(&Pubkey::new_unique()), None);
+    }
+
+    #[test]
+    fn decoding_rejects_incomplete_updates() {
+        let keys = keys();
+        let mut garbled = update(&keys, BALANCES, 0);
+        garbled.transaction.as_mut().unwrap().transaction = vec![1, 2, 3];
+        assert!(TransactionEvent::try_from(garbled).is_err());
+
+        let mut without_meta = update(&keys, BALANCES, 0);
+        without_meta.transaction.as_mut().unwrap().meta = None;
+        assert!(TransactionEvent::try_from(without_meta).is_err());
+
+        // Without the message the balances cannot be matched to accounts.
+        let mut without_message = update(&keys, BALANCES, 0);
+        without_message.transaction.as_mut().unwrap().transaction = vec![];
+        let event = TransactionEvent::try_from(without_message).unwrap();
+        assert!(event.account_keys.is_empty());
+        assert_eq!(event.balance_delta(&keys.recipient), None);
+    }
+
+    #[test]
+    fn balance_conditions_match_loaded_accounts() {
+        let keys = keys();
+        let event = event(&keys, BALANCES, 0);
+
+        let increase = |account, min| TransactionCondition::BalanceIncrease { account, min };
+        let decrease = |account, min| TransactionCondition::BalanceDecrease { account, min };
+        assert!(fired(vec![increase(keys.recipient, 600_000)], &event));
+        assert!(!fired(vec![increase(keys.recipient, 600_001)], &event));
+        assert!(fired(vec![increase(keys.loaded_writable, 0)], &event));
+        assert!(!fired(vec![increase(keys.loaded_readonly, 0)], &event));
+        assert!(!fired(vec![increase(keys.payer, 0)], &event));
+        assert!(fired(vec![decrease(keys.payer, 605_001)], &event));
+        assert!(!fired(vec![decrease(keys.payer, 605_002)], &event));
+        assert!(!fired(vec![decrease(keys.recipient, 0)], &event));
+    }
+
+    #[test]
+    fn failed_transactions_only_match_failed() {
+        let keys = keys();
+        let succeeded = event(&keys, BALANCES, 0);
+        let failed = event(&keys, BALANCES, 1);
+
+        assert!(failed.failed);
+        assert!(fired(vec![TransactionCondition::Succeeded], &succeeded));
+        assert!(!fired(vec![TransactionCondition::Succeeded], &failed));
+        assert!(fired(vec![TransactionCondition::Failed], &failed));
+        assert!(!fired(vec![TransactionCondition::Failed], &succeeded));
+    }
+
+    #[test]
+    fn every_condition_of_a_rule_must_hold() {
+        let keys = keys();
+        let event = event(&keys, BALANCES, 1);
+        let slippage = TransactionCondition::LogContains("slippage".to_string());
+
+        assert!(fired(
+            vec![TransactionCondition::Failed, slippage.clone()],
+            &event
+        ));
+        assert!(fired(vec![TransactionCondition::MinFee(5_000)], &event));
+        assert!(!fired(
+            vec![slippage.clone(), TransactionCondition::MinFee(5_001)],
+            &event
+        ));
+        assert!(!fired(
+            vec![TransactionCondition::LogContains("swap".to_string())],
+            &event
+        ));
+    }
+
+    #[test]
+    fn vote_transactions_never_fire() {
+        let keys = keys();
+        let mut event = event(&keys, BALANCES, 0);
+        event.is_vote = true;
+
+        assert!(!fired(vec![TransactionCondition::Succeeded], &event));
+    }
+
+    #[test]
+    fn rules_parse_from_yaml() {
+        let yaml = r#"
+- name: forward-incoming
+  conditions:
+    - succeeded
+    - balance_increase:
+        account: "11111111111111111111111111111111"
+        min: 1000000
+  action:
+    transfer:
+      amount: 500000
+"#;
+        let rules: Vec<TransactionRule> = serde_yaml::from_str(yaml).unwrap();
+
+        assert!(matches!(
+            rules[0].conditions[..],
+            [
+                TransactionCondition::Succeeded,
+                TransactionCondition::BalanceIncrease { min: 1_000_000, .. }
+            ]
+        ));
+        assert_eq!(
+            rules[0].action,
+            Action::Transfer {
+                amount: Some(500_000)
+            }
+        );
+    }
+}
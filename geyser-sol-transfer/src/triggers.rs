@@ -0,0 +1,264 @@
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::geyser::SubscribeUpdateBlock;
+
+/// A named rule from `config.yaml` that runs `action` when `condition` holds.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TriggerRule {
+    pub name: String,
+    #[serde(with = "serde_yaml::with::singleton_map")]
+    pub condition: Condition,
+    #[serde(default, with = "serde_yaml::with::singleton_map")]
+    pub action: Action,
+}
+
+/// When a rule fires, evaluated against every block.
+///
+/// Leaders can skip slots, so slot based conditions fire on the first block
+/// at or past their target instead of requiring an exact match.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum Condition {
+    /// At least this many slots after the rule last fired.
+    EverySlots(u64),
+    /// Block heights with `height % modulus == remainder`.
+    BlockHeight {
+        modulus: u64,
+        #[serde(default)]
+        remainder: u64,
+    },
+    /// Blocks executing more than this many transactions.
+    MinTransactions(u64),
+    /// At least this many seconds of block time after the rule last fired.
+    IntervalSecs(u64),
+    /// Once for each of these slots.
+    Slots(Vec<u64>),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// Sends `amount` lamports, `solana.transfer_amount` when unset.
+    Transfer {
+        #[serde(default)]
+        amount: Option<u64>,
+    },
+    /// Only logs that the rule fired.
+    #[default]
+    Log,
+}
+
+/// Evaluates the configured rules against incoming blocks.
+pub struct TriggerEngine {
+    rules: Vec<TriggerRule>,
+    last_fired: Vec<Option<Fired>>,
+    last_slot: Option<u64>,
+}
+
+#[derive(Clone, Copy)]
+struct Fired {
+    slot: u64,
+    time: i64,
+}
+
+impl TriggerEngine {
+    pub fn new(rules: Vec<TriggerRule>) -> Self {
+        let last_fired = vec![None; rules.len()];
+        Self {
+            rules,
+            last_fired,
+            last_slot: None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Returns the rules `block` fires, in config order.
+    pub fn evaluate(&mut self, block: &SubscribeUpdateBlock) -> Vec<&TriggerRule> {
+        let now = Fired {
+            slot: block.slot,
+            time: block
+                .block_time
+                .as_ref()
+                .map(|time| time.timestamp)
+                .unwrap_or_else(unix_now),
+        };
+
+        // Targets in skipped slots are caught up by the next block; before the
+        // first block the engine only looks at the current slot. Blocks
+        // replayed after a reconnect never move the last slot back.
+        let unseen = self.last_slot.map_or(now.slot, |slot| slot + 1)..=now.slot;
+        self.last_slot = Some(self.last_slot.map_or(now.slot, |slot| slot.max(now.slot)));
+
+        let mut fired = Vec::new();
+        for (rule, last) in self.rules.iter().zip(self.last_fired.iter_mut()) {
+            if rule.condition.matches(block, now, *last, &unseen) {
+                *last = Some(now);
+                fired.push(rule);
+            }
+        }
+        fired
+    }
+}
+
+impl Condition {
+    fn matches(
+        &self,
+        block: &SubscribeUpdateBlock,
+        now: Fired,
+        last: Option<Fired>,
+        unseen: &RangeInclusive<u64>,
+    ) -> bool {
+        match self {
+            Condition::EverySlots(slots) => {
+                last.is_none_or(|last| now.slot >= last.slot.saturating_add(*slots))
+            }
+            Condition::BlockHeight { modulus, remainder } => {
+                *modulus > 0 && block.block_height % modulus == *remainder
+            }
+            Condition::MinTransactions(threshold) => block.executed_transaction_count > *threshold,
+            Condition::IntervalSecs(seconds) => {
+                last.is_none_or(|last| now.time >= last.time.saturating_add(*seconds as i64))
+            }
+            Condition::Slots(slots) => slots.iter().any(|slot| unseen.contains(slot)),
+        }
+    }
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geyser::SubscribeUpdateBlockTime;
+
+    fn block(slot: u64, block_height: u64, transactions: u64, time: i64) -> SubscribeUpdateBlock {
+        SubscribeUpdateBlock {
+            slot,
+            block_height,
+            executed_transaction_count: transactions,
+            block_time: Some(SubscribeUpdateBlockTime { timestamp: time }),
+            ..Default::default()
+        }
+    }
+
+    fn engine(condition: Condition) -> TriggerEngine {
+        TriggerEngine::new(vec![TriggerRule {
+            name: "rule".to_string(),
+            condition,
+            action: Action::Log,
+        }])
+    }
+
+    /// Whether the single rule of `engine` fires for a block at `slot`.
+    fn fires_at(engine: &mut TriggerEngine, slot: u64) -> bool {
+        !engine.evaluate(&block(slot, slot, 0, 0)).is_empty()
+    }
+
+    #[test]
+    fn every_slots_fires_on_first_block_then_after_the_interval() {
+        let mut engine = engine(Condition::EverySlots(100));
+
+        assert!(fires_at(&mut engine, 1_000));
+        assert!(!fires_at(&mut engine, 1_050));
+        assert!(!fires_at(&mut engine, 1_099));
+        assert!(fires_at(&mut engine, 1_100));
+        // A gap past the interval fires once, counting from the late block.
+        assert!(fires_at(&mut engine, 1_350));
+        assert!(!fires_at(&mut engine, 1_400));
+        assert!(fires_at(&mut engine, 1_450));
+    }
+
+    #[test]
+    fn block_height_matches_modulus_and_remainder() {
+        let mut engine = engine(Condition::BlockHeight {
+            modulus: 10,
+            remainder: 3,
+        });
+        assert!(engine.evaluate(&block(1, 13, 0, 0)).len() == 1);
+        assert!(engine.evaluate(&block(2, 14, 0, 0)).is_empty());
+        assert!(engine.evaluate(&block(3, 23, 0, 0)).len() == 1);
+
+        let mut engine = self::engine(Condition::BlockHeight {
+            modulus: 0,
+            remainder: 0,
+        });
+        assert!(engine.evaluate(&block(1, 0, 0, 0)).is_empty());
+    }
+
+    #[test]
+    fn min_transactions_needs_more_than_the_threshold() {
+        let mut engine = engine(Condition::MinTransactions(100));
+
+        assert!(engine.evaluate(&block(1, 1, 100, 0)).is_empty());
+        assert_eq!(engine.evaluate(&block(2, 2, 101, 0)).len(), 1);
+        assert_eq!(engine.evaluate(&block(3, 3, 5_000, 0)).len(), 1);
+    }
+
+    #[test]
+    fn interval_secs_uses_block_time() {
+        let mut engine = engine(Condition::IntervalSecs(60));
+
+        assert_eq!(engine.evaluate(&block(1, 1, 0, 1_000)).len(), 1);
+        assert!(engine.evaluate(&block(2, 2, 0, 1_030)).is_empty());
+        assert!(engine.evaluate(&block(3, 3, 0, 1_059)).is_empty());
+        assert_eq!(engine.evaluate(&block(4, 4, 0, 1_060)).len(), 1);
+        assert!(engine.evaluate(&block(5, 5, 0, 1_100)).is_empty());
+    }
+
+    #[test]
+    fn slots_only_look_at_the_current_slot_before_the_first_block() {
+        let mut engine = engine(Condition::Slots(vec![5, 7]));
+
+        // Slot 5 passed before the engine saw any block.
+        assert!(!fires_at(&mut engine, 6));
+        assert!(fires_at(&mut engine, 7));
+        assert!(!fires_at(&mut engine, 8));
+    }
+
+    #[test]
+    fn slots_skipped_by_a_gap_fire_once_on_the_next_block() {
+        let mut engine = engine(Condition::Slots(vec![10, 11, 20]));
+
+        assert!(!fires_at(&mut engine, 9));
+        // A reconnect that resumes past both 10 and 11 fires once for them.
+        assert!(fires_at(&mut engine, 15));
+        // Blocks replayed after a reconnect do not fire their slots again.
+        assert!(!fires_at(&mut engine, 10));
+        assert!(!fires_at(&mut engine, 15));
+        assert!(!fires_at(&mut engine, 19));
+        assert!(fires_at(&mut engine, 20));
+    }
+
+    #[test]
+    fn rules_fire_in_config_order() {
+        let yaml = r#"
+- name: every-slot
+  condition:
+    every_slots: 1
+  action:
+    transfer:
+      amount: 5000
+- name: busy
+  condition:
+    min_transactions: 10
+"#;
+        let rules: Vec<TriggerRule> = serde_yaml::from_str(yaml).unwrap();
+        let mut engine = TriggerEngine::new(rules);
+
+        let fired = engine.evaluate(&block(1, 1, 11, 0));
+        let names: Vec<_> = fired.iter().map(|rule| rule.name.as_str()).collect();
+        assert_eq!(names, ["every-slot", "busy"]);
+        assert_eq!(fired[0].action, Action::Transfer { amount: Some(5000) });
+        assert_eq!(fired[1].action, Action::Log);
+    }
+}
@@ -0,0 +1,388 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use solana_deposit_interface::event::Event;
+use solana_sdk::pubkey::Pubkey;
+
+/// Decodes the events `program_id` logged in a transaction's log messages.
+///
+/// Data lines are attributed to the innermost program running when they were
+/// logged, so events another program logs through CPI are skipped, as are
+/// records this client cannot decode.
+pub fn parse_events(program_id: &Pubkey, logs: &[String]) -> Vec<Event> {
+    let program_id = program_id.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for line in logs {
+        if let Some(data) = line.strip_prefix("Program data: ") {
+            if stack.last() != Some(&program_id.as_str()) {
+                continue;
+            }
+            for field in data.split(' ') {
+                if let Some(event) = STANDARD
+                    .decode(field)
+                    .ok()
+                    .and_then(|data| Event::decode(&data))
+                {
+                    events.push(event);
+                }
+            }
+        } else if let Some(rest) = line.strip_prefix("Program ") {
+            let mut words = rest.split(' ');
+            let (Some(id), Some(status)) = (words.next(), words.next()) else {
+                continue;
+            };
+            if status == "invoke" {
+                stack.push(id);
+            } else if status == "success" || status.starts_with("failed") {
+                stack.pop();
+            }
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_deposit_interface::{
+        event::{EventKind, EVENT_VERSION},
+        find_program_data_address, instruction,
+    };
+    use solana_program_test::{
+        get_runtime_environment_key, invoke_builtin_function, EbpfError, EbpfVm, InvokeContext,
+        ProgramTest, ProgramTestContext,
+    };
+    use solana_sdk::{
+        account::Account,
+        account_info::AccountInfo,
+        bpf_loader_upgradeable,
+        entrypoint::ProgramResult,
+        instruction::Instruction,
+        native_token::LAMPORTS_PER_SOL,
+        program_stubs::{set_syscall_stubs, SyscallStubs},
+        signature::{Keypair, Signer},
+        system_program,
+        transaction::Transaction,
+    };
+    use std::{cell::RefCell, sync::Once};
+
+    fn event(kind: EventKind, amount: u64) -> Event {
+        Event {
+            kind,
+            user: Pubkey::new_unique(),
+            amount,
+            balance: 0,
+            slot: 1,
+        }
+    }
+
+    fn data_line(events: &[&Event]) -> String {
+        let fields: Vec<_> = events
+            .iter()
+            .map(|event| STANDARD.encode(event.encode()))
+            .collect();
+        format!("Program data: {}", fields.join(" "))
+    }
+
+    fn logs(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn decodes_events_logged_by_the_program() {
+        let program_id = Pubkey::new_unique();
+        let deposit = event(EventKind::Deposit, 5);
+        let release = event(EventKind::EscrowRelease, 7);
+        let logs = logs(&[
+            &format!("Program {} invoke [1]", program_id),
+            "Program log: running deposit",
+            &data_line(&[&deposit, &release]),
+            &format!(
+                "Program {} consumed 5000 of 200000 compute units",
+                program_id
+            ),
+            &format!("Program {} success", program_id),
+        ]);
+
+        assert_eq!(parse_events(&program_id, &logs), [deposit, release]);
+    }
+
+    #[test]
+    fn skips_data_logged_by_other_programs() {
+        let program_id = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let ours = event(EventKind::Withdraw, 1);
+        let theirs = event(EventKind::Deposit, 2);
+        let logs = logs(&[
+            &format!("Program {} invoke [1]", other),
+            &data_line(&[&theirs]),
+            &format!("Program {} invoke [2]", program_id),
+            &data_line(&[&ours]),
+            &format!("Program {} success", program_id),
+            &data_line(&[&theirs]),
+            &format!("Program {} success", other),
+            &data_line(&[&theirs]),
+        ]);
+
+        assert_eq!(parse_events(&program_id, &logs), [ours]);
+    }
+
+    #[test]
+    fn failed_invocations_leave_the_program() {
+        let program_id = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let first = event(EventKind::Deposit, 1);
+        let second = event(EventKind::Deposit, 2);
+        let logs = logs(&[
+            &format!("Program {} invoke [1]", program_id),
+            &data_line(&[&first]),
+            &format!("Program {} invoke [2]", other),
+            &format!("Program {} failed: custom program error: 0x1", other),
+            &data_line(&[&second]),
+            &format!("Program {} success", program_id),
+        ]);
+
+        assert_eq!(parse_events(&program_id, &logs), [first, second]);
+    }
+
+    #[test]
+    fn skips_records_it_cannot_decode() {
+        let program_id = Pubkey::new_unique();
+        let known = event(EventKind::ClaimRewards, 3);
+        let mut newer = known.encode();
+        newer[0] = EVENT_VERSION + 1;
+        let logs = logs(&[
+            &format!("Program {} invoke [1]", program_id),
+            &format!("Program data: {}", STANDARD.encode(newer)),
+            "Program data: not-base64!",
+            &format!("Program data: {}", STANDARD.encode([1u8, 200])),
+            &data_line(&[&known]),
+            &format!("Program {} success", program_id),
+        ]);
+
+        assert_eq!(parse_events(&program_id, &logs), [known]);
+        assert!(parse_events(&program_id, &[]).is_empty());
+    }
+
+    thread_local! {
+        static LOGGED_DATA: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    /// Stands in for the syscall stubs while they are being swapped.
+    struct NoStubs;
+    impl SyscallStubs for NoStubs {}
+
+    /// Program-test's native syscall stubs print `sol_log_data` records to
+    /// stdout instead of the transaction logs. This forwards everything to
+    /// them but keeps the records for the processor to log.
+    struct LogDataStubs(Box<dyn SyscallStubs>);
+
+    impl SyscallStubs for LogDataStubs {
+        fn sol_log(&self, message: &str) {
+            self.0.sol_log(message)
+        }
+        fn sol_invoke_signed(
+            &self,
+            instruction: &Instruction,
+            account_infos: &[AccountInfo],
+            signers_seeds: &[&[&[u8]]],
+        ) -> ProgramResult {
+            self.0
+                .sol_invoke_signed(instruction, account_infos, signers_seeds)
+        }
+        fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+            self.0.sol_get_clock_sysvar(var_addr)
+        }
+        fn sol_get_epoch_schedule_sysvar(&self, var_addr: *mut u8) -> u64 {
+            self.0.sol_get_epoch_schedule_sysvar(var_addr)
+        }
+        fn sol_get_epoch_rewards_sysvar(&self, var_addr: *mut u8) -> u64 {
+            self.0.sol_get_epoch_rewards_sysvar(var_addr)
+        }
+        fn sol_get_fees_sysvar(&self, var_addr: *mut u8) -> u64 {
+            self.0.sol_get_fees_sysvar(var_addr)
+        }
+        fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+            self.0.sol_get_rent_sysvar(var_addr)
+        }
+        fn sol_get_last_restart_slot(&self, var_addr: *mut u8) -> u64 {
+            self.0.sol_get_last_restart_slot(var_addr)
+        }
+        fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+            self.0.sol_get_return_data()
+        }
+        fn sol_set_return_data(&self, data: &[u8]) {
+            self.0.sol_set_return_data(data)
+        }
+        fn sol_get_stack_height(&self) -> u64 {
+            self.0.sol_get_stack_height()
+        }
+        fn sol_log_data(&self, fields: &[&[u8]]) {
+            let fields: Vec<_> = fields.iter().map(|field| STANDARD.encode(field)).collect();
+            LOGGED_DATA.with(|logged| logged.borrow_mut().push(fields.join(" ")));
+        }
+    }
+
+    /// Starts a bank running the deposit program, with the fee payer as its
+    /// upgrade authority and `user` funded.
+    async fn start(program_id: Pubkey, user: &Keypair) -> ProgramTestContext {
+        // `processor!`, plus writing the kept records to the transaction
+        // logs as `Program data:` lines like the runtime's `sol_log_data`.
+        let mut program_test = ProgramTest::new(
+            "solana_deposit",
+            program_id,
+            Some(|vm, _arg0, _arg1, _arg2, _arg3, _arg4| {
+                let vm = unsafe {
+                    &mut *((vm as *mut u64).offset(-(get_runtime_environment_key() as isize))
+                        as *mut EbpfVm<InvokeContext>)
+                };
+                let result = invoke_builtin_function(
+                    solana_deposit::process_instruction,
+                    vm.context_object_pointer,
+                );
+                if let Some(log_collector) = vm.context_object_pointer.get_log_collector() {
+                    for data in LOGGED_DATA.with(RefCell::take) {
+                        log_collector
+                            .borrow_mut()
+                            .log(&format!("Program data: {}", data));
+                    }
+                }
+                vm.program_result = result.map_err(EbpfError::SyscallError).into();
+            }),
+        );
+        program_test.add_account(
+            user.pubkey(),
+            Account::new(10 * LAMPORTS_PER_SOL, 0, &system_program::id()),
+        );
+        let mut context = program_test.start_with_context().await;
+
+        // After the start above, which installs program-test's stubs.
+        static WRAP_STUBS: Once = Once::new();
+        WRAP_STUBS.call_once(|| {
+            let native = set_syscall_stubs(Box::new(NoStubs));
+            set_syscall_stubs(Box::new(LogDataStubs(native)));
+        });
+
+        // ProgramData as the upgradeable loader writes it, so `Initialize`
+        // accepts the payer as admin. Funded out of the payer to keep the
+        // bank's capitalization consistent across warps.
+        let payer = context.payer.pubkey();
+        let mut data = 3u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(payer.as_ref());
+        let rent = context.banks_client.get_rent().await.unwrap();
+        let mut account = Account::new(
+            rent.minimum_balance(data.len()),
+            data.len(),
+            &bpf_loader_upgradeable::id(),
+        );
+        account.data = data;
+        let mut payer_account = context
+            .banks_client
+            .get_account(payer)
+            .await
+            .unwrap()
+            .unwrap();
+        payer_account.lamports -= account.lamports;
+        context.set_account(&payer, &payer_account.into());
+        context.set_account(&find_program_data_address(&program_id).0, &account.into());
+        context
+    }
+
+    /// Processes `instructions` at `slot` and decodes the events in its logs.
+    async fn run(
+        context: &mut ProgramTestContext,
+        slot: u64,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Vec<Event> {
+        context.warp_to_slot(slot).unwrap();
+        let program_id = instructions[0].program_id;
+        let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+        let mut all_signers = vec![&context.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&context.payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+        let processed = context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+            .unwrap();
+        processed.result.unwrap();
+        parse_events(&program_id, &processed.metadata.unwrap().log_messages)
+    }
+
+    #[tokio::test]
+    async fn decodes_deposit_and_withdraw_from_the_bank() {
+        let program_id = Pubkey::new_unique();
+        let user = Keypair::new();
+        let mut context = start(program_id, &user).await;
+        let admin = context.payer.pubkey();
+        let ix = instruction::initialize(&program_id, &admin);
+        run(&mut context, 2, &[ix], &[]).await;
+
+        let ix = instruction::deposit(&program_id, &user.pubkey(), 3 * LAMPORTS_PER_SOL);
+        let events = run(&mut context, 10, &[ix], &[&user]).await;
+        assert_eq!(
+            events,
+            [Event {
+                kind: EventKind::Deposit,
+                user: user.pubkey(),
+                amount: 3 * LAMPORTS_PER_SOL,
+                balance: 3 * LAMPORTS_PER_SOL,
+                slot: 10,
+            }]
+        );
+
+        let ix = instruction::withdraw(&program_id, &user.pubkey(), LAMPORTS_PER_SOL);
+        let events = run(&mut context, 20, &[ix], &[&user]).await;
+        assert_eq!(
+            events,
+            [Event {
+                kind: EventKind::Withdraw,
+                user: user.pubkey(),
+                amount: LAMPORTS_PER_SOL,
+                balance: 2 * LAMPORTS_PER_SOL,
+                slot: 20,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn decodes_admin_actions_from_the_bank() {
+        let program_id = Pubkey::new_unique();
+        let user = Keypair::new();
+        let mut context = start(program_id, &user).await;
+        let admin = context.payer.pubkey();
+
+        let ix = instruction::initialize(&program_id, &admin);
+        let events = run(&mut context, 5, &[ix], &[]).await;
+        assert_eq!(
+            events,
+            [Event {
+                kind: EventKind::Initialize,
+                user: admin,
+                amount: 0,
+                balance: 0,
+                slot: 5,
+            }]
+        );
+
+        let pause = instruction::set_paused(&program_id, &admin, true, false);
+        let resume = instruction::set_paused(&program_id, &admin, false, false);
+        let events = run(&mut context, 12, &[pause, resume], &[]).await;
+        let event = |amount| Event {
+            kind: EventKind::SetPaused,
+            user: admin,
+            amount,
+            balance: 0,
+            slot: 12,
+        };
+        assert_eq!(events, [event(1), event(0)]);
+    }
+}
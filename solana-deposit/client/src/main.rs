@@ -1,27 +1,42 @@
-use borsh::{BorshDeserialize, BorshSerialize};
-use serde::{Deserialize, Serialize};
+mod events;
+
+use borsh::BorshDeserialize;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::client_error::ClientError;
 use solana_client::rpc_client::RpcClient;
+use solana_client::{
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_deposit_interface::{
+    error::DepositError,
+    find_allowlist_address, find_config_address, find_escrow_address, find_ledger_address,
+    find_multisig_address, find_proposal_address, find_share_mint_address,
+    find_token_ledger_address, find_vault_address, find_withdrawal_address,
+    get_share_account_address, instruction,
+    state::{
+        Config, Delegation, DepositAccount, Escrow, Multisig, PendingWithdrawal,
+        TokenDepositAccount, VestingSchedule, WithdrawalProposal,
+    },
+};
 use solana_sdk::{
     commitment_config::CommitmentConfig,
-    instruction::{AccountMeta, Instruction},
+    instruction::{Instruction, InstructionError},
+    native_token::{lamports_to_sol, sol_to_lamports, LAMPORTS_PER_SOL},
+    program_pack::Pack,
     pubkey::Pubkey,
-    signature::Keypair,
+    signature::{Keypair, Signature},
     signer::Signer,
-    system_program,
-    transaction::Transaction,
+    transaction::{Transaction, TransactionError},
+};
+use solana_transaction_status::UiTransactionEncoding;
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
 };
+use spl_token::state::Mint;
 use std::str::FromStr;
 
-#[derive(BorshSerialize, BorshDeserialize)]
-pub struct DepositAccount {
-    pub balance: u64,
-}
-
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
-pub enum ProgramInstruction {
-    Deposit { amount: u64 },
-    Withdraw { amount: u64 },
-}
+use events::parse_events;
 
 pub struct Client {
     rpc_client: RpcClient,
@@ -56,119 +71,1445 @@ impl Client {
         })
     }
 
-    pub async fn deposit(
+    /// Program-derived vault that holds every deposit made through the program.
+    pub fn vault_address(&self) -> Pubkey {
+        find_vault_address(&self.program_id).0
+    }
+
+    /// Program-owned ledger recording how much of the vault belongs to `user`.
+    pub fn ledger_address(&self, user: &Pubkey) -> Pubkey {
+        find_ledger_address(&self.program_id, user).0
+    }
+
+    /// Fetches and decodes the global program config.
+    pub fn fetch_config(&self) -> Result<Config, Box<dyn std::error::Error>> {
+        let address = find_config_address(&self.program_id).0;
+        let data = self
+            .rpc_client
+            .get_account_data(&address)
+            .map_err(|e| format!("Err: program not initialized, config {}: {}", address, e))?;
+        Ok(Config::deserialize(&mut data.as_slice())
+            .map_err(|e| format!("Err: decode config {}: {}", address, e))?)
+    }
+
+    /// Fetches the ledger of `user`, or `None` before their first deposit.
+    pub fn fetch_ledger(
+        &self,
+        user: &Pubkey,
+    ) -> Result<Option<DepositAccount>, Box<dyn std::error::Error>> {
+        let ledger = self.ledger_address(user);
+        match self.rpc_client.get_account_data(&ledger) {
+            Ok(data) => Ok(Some(
+                DepositAccount::deserialize(&mut data.as_slice())
+                    .map_err(|e| format!("Err: decode ledger {}: {}", ledger, e))?,
+            )),
+            Err(_) => Ok(None),
+        }
+    }
+
+    pub async fn deposit(&self, amount_sol: f64) -> Result<(), Box<dyn std::error::Error>> {
+        let amount = sol_to_lamports(amount_sol);
+
+        let config = self.fetch_config()?;
+        let fee = config.deposit_fee(amount);
+        print_fee_breakdown(amount, fee, "credited")?;
+
+        let payer = self.payer.pubkey();
+        let mut instructions = Vec::new();
+        if config.shares_enabled {
+            instructions.push(create_associated_token_account_idempotent(
+                &payer,
+                &payer,
+                &find_share_mint_address(&self.program_id).0,
+                &spl_token::id(),
+            ));
+        }
+        instructions.push(instruction::deposit(&self.program_id, &payer, amount));
+        let signature = self.send(&instructions)?;
+        println!("Dep compilte: {}", signature);
+        Ok(())
+    }
+
+    pub async fn withdraw(&self, amount_sol: f64) -> Result<(), Box<dyn std::error::Error>> {
+        let amount = sol_to_lamports(amount_sol);
+
+        let fee = self.fetch_config()?.withdraw_fee(amount);
+        print_fee_breakdown(amount, fee, "received")?;
+
+        let instruction = instruction::withdraw(&self.program_id, &self.payer.pubkey(), amount);
+        let signature = self.send(&[instruction])?;
+        println!("Withdrawal completed: {}", signature);
+        Ok(())
+    }
+
+    pub async fn initialize(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let instruction = instruction::initialize(&self.program_id, &self.payer.pubkey());
+        let signature = self.send(&[instruction])?;
+        println!("Initialized, admin {}: {}", self.payer.pubkey(), signature);
+        Ok(())
+    }
+
+    pub async fn set_authority(
+        &self,
+        new_authority: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let new_authority = parse_pubkey(new_authority)?;
+
+        let instruction =
+            instruction::set_authority(&self.program_id, &self.payer.pubkey(), &new_authority);
+        let signature = self.send(&[instruction])?;
+        println!("Authority nominated {}: {}", new_authority, signature);
+        Ok(())
+    }
+
+    pub async fn accept_authority(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let instruction = instruction::accept_authority(&self.program_id, &self.payer.pubkey());
+        let signature = self.send(&[instruction])?;
+        println!(
+            "Authority accepted by {}: {}",
+            self.payer.pubkey(),
+            signature
+        );
+        Ok(())
+    }
+
+    pub async fn set_paused(
+        &self,
+        paused: bool,
+        allow_withdrawals: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let instruction = instruction::set_paused(
+            &self.program_id,
+            &self.payer.pubkey(),
+            paused,
+            allow_withdrawals,
+        );
+        let signature = self.send(&[instruction])?;
+        println!(
+            "Paused: {} withdrawals allowed: {}: {}",
+            paused, allow_withdrawals, signature
+        );
+        Ok(())
+    }
+
+    pub async fn set_withdraw_delay(&self, seconds: i64) -> Result<(), Box<dyn std::error::Error>> {
+        let instruction =
+            instruction::set_withdraw_delay(&self.program_id, &self.payer.pubkey(), seconds);
+        let signature = self.send(&[instruction])?;
+        println!("Withdraw delay {}s: {}", seconds, signature);
+        Ok(())
+    }
+
+    pub async fn request_withdraw(
         &self,
-        deposit_account: &str,
         amount_sol: f64,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let amount = (amount_sol * 1_000_000_000.0) as u64;
+        let amount = sol_to_lamports(amount_sol);
+        let user = self.payer.pubkey();
+        let id = self
+            .fetch_ledger(&user)?
+            .ok_or("Err: no ledger, deposit first")?
+            .next_withdrawal_id;
 
-        let deposit_pubkey = Pubkey::from_str(deposit_account).map_err(|e| {
-            format!(
-                "Err: parsing pb key {} : {}",
-                deposit_account, e
-            )
-        })?;
+        let instruction = instruction::request_withdraw(&self.program_id, &user, id, amount);
+        let signature = self.send(&[instruction])?;
+        println!("Withdrawal {} requested: {}", id, signature);
+        Ok(())
+    }
+
+    pub async fn claim_withdraw(&self, id: u64) -> Result<(), Box<dyn std::error::Error>> {
+        let address = find_withdrawal_address(&self.program_id, &self.payer.pubkey(), id).0;
+        let data = self
+            .rpc_client
+            .get_account_data(&address)
+            .map_err(|e| format!("Err: no pending withdrawal #{}: {}", id, e))?;
+        let pending = PendingWithdrawal::deserialize(&mut data.as_slice())
+            .map_err(|e| format!("Err: decode withdrawal {}: {}", address, e))?;
+        let fee = self.fetch_config()?.withdraw_fee(pending.amount);
+        print_fee_breakdown(pending.amount, fee, "received")?;
+
+        let instruction = instruction::claim_withdraw(&self.program_id, &self.payer.pubkey(), id);
+        let signature = self.send(&[instruction])?;
+        println!("Withdrawal {} claimed: {}", id, signature);
+        Ok(())
+    }
 
-        let mut instruction_data = vec![0u8];
-        instruction_data.extend_from_slice(&amount.to_le_bytes());
+    pub async fn cancel_withdraw(&self, id: u64) -> Result<(), Box<dyn std::error::Error>> {
+        let instruction = instruction::cancel_withdraw(&self.program_id, &self.payer.pubkey(), id);
+        let signature = self.send(&[instruction])?;
+        println!("Withdrawal {} cancelled: {}", id, signature);
+        Ok(())
+    }
 
-        let instruction = Instruction::new(
-            self.program_id,
-            &instruction_data,
-            vec![
-                AccountMeta::new(self.payer.pubkey(), true),
-                AccountMeta::new(deposit_pubkey, false),
-                AccountMeta::new_readonly(system_program::id(), false),
-            ],
+    pub async fn set_fees(
+        &self,
+        deposit_fee_bps: u16,
+        withdraw_fee_bps: u16,
+        min_fee_sol: f64,
+        fee_collector: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let fee_collector = parse_pubkey(fee_collector)?;
+        let min_fee = sol_to_lamports(min_fee_sol);
+
+        let instruction = instruction::set_fees(
+            &self.program_id,
+            &self.payer.pubkey(),
+            deposit_fee_bps,
+            withdraw_fee_bps,
+            min_fee,
+            &fee_collector,
         );
+        let signature = self.send(&[instruction])?;
+        println!(
+            "Fees: deposit {} bps withdraw {} bps min {} SOL collector {}: {}",
+            deposit_fee_bps, withdraw_fee_bps, min_fee_sol, fee_collector, signature
+        );
+        Ok(())
+    }
 
-        let blockhash = self.rpc_client.get_latest_blockhash()?;
-        let transaction = Transaction::new_signed_with_payer(
-            &[instruction],
-            Some(&self.payer.pubkey()),
-            &[&self.payer],
-            blockhash,
+    pub async fn collect_fees(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let accrued = self.fetch_config()?.accrued_fees;
+
+        let instruction = instruction::collect_fees(&self.program_id, &self.payer.pubkey());
+        let signature = self.send(&[instruction])?;
+        println!(
+            "Fees collected {} SOL: {}",
+            lamports_to_sol(accrued),
+            signature
         );
+        Ok(())
+    }
 
-        let signature = self.rpc_client.send_and_confirm_transaction(&transaction)?;
-        println!("Dep compilte: {}", signature);
+    /// Lists the pending withdrawals of `user` (the payer by default).
+    pub async fn list_withdrawals(
+        &self,
+        user: Option<&str>,
+    ) -> Result<Vec<PendingWithdrawal>, Box<dyn std::error::Error>> {
+        let user = match user {
+            Some(user) => parse_pubkey(user)?,
+            None => self.payer.pubkey(),
+        };
+
+        let accounts = self.rpc_client.get_program_accounts_with_config(
+            &self.program_id,
+            RpcProgramAccountsConfig {
+                filters: Some(vec![
+                    RpcFilterType::DataSize(PendingWithdrawal::LEN as u64),
+                    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, user.as_ref())),
+                ]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    ..RpcAccountInfoConfig::default()
+                },
+                ..RpcProgramAccountsConfig::default()
+            },
+        )?;
+
+        let mut withdrawals = accounts
+            .into_iter()
+            .map(|(address, account)| {
+                PendingWithdrawal::deserialize(&mut account.data.as_slice())
+                    .map_err(|e| format!("Err: decode withdrawal {}: {}", address, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        withdrawals.sort_by_key(|w| w.id);
+
+        let now = self
+            .rpc_client
+            .get_block_time(self.rpc_client.get_slot()?)?;
+        for w in &withdrawals {
+            let status = if now >= w.unlock_at {
+                "claimable".to_string()
+            } else {
+                format!("unlocks in {}s", w.unlock_at - now)
+            };
+            println!(
+                "#{} {} SOL, unlock at {} ({})",
+                w.id,
+                lamports_to_sol(w.amount),
+                w.unlock_at,
+                status
+            );
+        }
+        if withdrawals.is_empty() {
+            println!("No pending withdrawals for {}", user);
+        }
+        Ok(withdrawals)
+    }
+
+    /// Fetches the multisig guarding the ledger of `owner`.
+    pub fn fetch_multisig(&self, owner: &Pubkey) -> Result<Multisig, Box<dyn std::error::Error>> {
+        let address = find_multisig_address(&self.program_id, owner).0;
+        let data = self
+            .rpc_client
+            .get_account_data(&address)
+            .map_err(|e| format!("Err: no multisig for {}: {}", owner, e))?;
+        Ok(Multisig::deserialize(&mut data.as_slice())
+            .map_err(|e| format!("Err: decode multisig {}: {}", address, e))?)
+    }
+
+    pub fn fetch_proposal(
+        &self,
+        owner: &Pubkey,
+        id: u64,
+    ) -> Result<WithdrawalProposal, Box<dyn std::error::Error>> {
+        let address = find_proposal_address(&self.program_id, owner, id).0;
+        let data = self
+            .rpc_client
+            .get_account_data(&address)
+            .map_err(|e| format!("Err: no proposal #{} for {}: {}", id, owner, e))?;
+        Ok(WithdrawalProposal::deserialize(&mut data.as_slice())
+            .map_err(|e| format!("Err: decode proposal {}: {}", address, e))?)
+    }
+
+    pub async fn set_multisig(
+        &self,
+        threshold: u8,
+        signers: &[String],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let signers = signers
+            .iter()
+            .map(|signer| parse_pubkey(signer))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let instruction =
+            instruction::set_multisig(&self.program_id, &self.payer.pubkey(), &signers, threshold);
+        let signature = self.send(&[instruction])?;
+        println!(
+            "Multisig {}-of-{} set: {}",
+            threshold,
+            signers.len(),
+            signature
+        );
         Ok(())
     }
 
-    pub async fn withdraw(
+    pub async fn propose(
         &self,
-        deposit_account: &str,
+        owner: &str,
         amount_sol: f64,
+        recipient: &str,
+        expires_in: i64,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let amount = (amount_sol * 1_000_000_000.0) as u64;
+        let owner = parse_pubkey(owner)?;
+        let recipient = parse_pubkey(recipient)?;
+        let amount = sol_to_lamports(amount_sol);
+        let id = self.fetch_multisig(&owner)?.next_proposal_id;
+
+        let instruction = instruction::propose_withdraw(
+            &self.program_id,
+            &self.payer.pubkey(),
+            &owner,
+            id,
+            amount,
+            &recipient,
+            expires_in,
+        );
+        let signature = self.send(&[instruction])?;
+        println!(
+            "Proposal {} of {} SOL to {}: {}",
+            id, amount_sol, recipient, signature
+        );
+        Ok(())
+    }
+
+    pub async fn approve(&self, owner: &str, id: u64) -> Result<(), Box<dyn std::error::Error>> {
+        let owner = parse_pubkey(owner)?;
+
+        let instruction =
+            instruction::approve_withdraw(&self.program_id, &self.payer.pubkey(), &owner, id);
+        let signature = self.send(&[instruction])?;
+        println!("Proposal {} approved: {}", id, signature);
+        Ok(())
+    }
+
+    pub async fn execute(&self, owner: &str, id: u64) -> Result<(), Box<dyn std::error::Error>> {
+        let owner = parse_pubkey(owner)?;
+        let proposal = self.fetch_proposal(&owner, id)?;
+        let fee = self.fetch_config()?.withdraw_fee(proposal.amount);
+        print_fee_breakdown(proposal.amount, fee, "received")?;
+
+        let instruction = instruction::execute_withdraw(
+            &self.program_id,
+            &owner,
+            id,
+            &proposal.proposer,
+            &proposal.recipient,
+        );
+        let signature = self.send(&[instruction])?;
+        println!("Proposal {} executed: {}", id, signature);
+        Ok(())
+    }
+
+    pub async fn cancel_proposal(
+        &self,
+        owner: &str,
+        id: u64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let owner = parse_pubkey(owner)?;
+        let proposal = self.fetch_proposal(&owner, id)?;
+
+        let instruction = instruction::cancel_proposal(
+            &self.program_id,
+            &self.payer.pubkey(),
+            &owner,
+            id,
+            &proposal.proposer,
+        );
+        let signature = self.send(&[instruction])?;
+        println!("Proposal {} cancelled: {}", id, signature);
+        Ok(())
+    }
+
+    /// Lists the open proposals against the ledger of `owner` (the payer by default).
+    pub async fn list_proposals(
+        &self,
+        owner: Option<&str>,
+    ) -> Result<Vec<WithdrawalProposal>, Box<dyn std::error::Error>> {
+        let owner = match owner {
+            Some(owner) => parse_pubkey(owner)?,
+            None => self.payer.pubkey(),
+        };
+        let multisig = self.fetch_multisig(&owner)?;
+
+        let accounts = self.rpc_client.get_program_accounts_with_config(
+            &self.program_id,
+            RpcProgramAccountsConfig {
+                filters: Some(vec![
+                    RpcFilterType::DataSize(WithdrawalProposal::LEN as u64),
+                    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, owner.as_ref())),
+                ]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    ..RpcAccountInfoConfig::default()
+                },
+                ..RpcProgramAccountsConfig::default()
+            },
+        )?;
+
+        let mut proposals = accounts
+            .into_iter()
+            .map(|(address, account)| {
+                WithdrawalProposal::deserialize(&mut account.data.as_slice())
+                    .map_err(|e| format!("Err: decode proposal {}: {}", address, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        proposals.sort_by_key(|p| p.id);
+
+        let now = self
+            .rpc_client
+            .get_block_time(self.rpc_client.get_slot()?)?;
+        for p in &proposals {
+            let status = if now > p.expires_at {
+                "expired".to_string()
+            } else if p.approval_count() < multisig.threshold as u32 {
+                "awaiting approvals".to_string()
+            } else if now < p.unlock_at {
+                format!("unlocks in {}s", p.unlock_at - now)
+            } else {
+                "executable".to_string()
+            };
+            let approvers = multisig
+                .signers
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| p.is_approved_by(*i))
+                .map(|(_, signer)| signer.to_string())
+                .collect::<Vec<_>>();
+            println!(
+                "#{} {} SOL to {}, {}/{} approvals [{}], expires at {} ({})",
+                p.id,
+                lamports_to_sol(p.amount),
+                p.recipient,
+                p.approval_count(),
+                multisig.threshold,
+                approvers.join(", "),
+                p.expires_at,
+                status
+            );
+        }
+        if proposals.is_empty() {
+            println!("No open proposals for {}", owner);
+        }
+        Ok(proposals)
+    }
+
+    pub async fn deposit_vested(
+        &self,
+        beneficiary: &str,
+        amount_sol: f64,
+        start: i64,
+        cliff: i64,
+        end: i64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let beneficiary = parse_pubkey(beneficiary)?;
+        let amount = sol_to_lamports(amount_sol);
+
+        let fee = self.fetch_config()?.deposit_fee(amount);
+        print_fee_breakdown(amount, fee, "vesting")?;
+
+        let instruction = instruction::deposit_vested(
+            &self.program_id,
+            &self.payer.pubkey(),
+            &beneficiary,
+            amount,
+            start,
+            cliff,
+            end,
+        );
+        let signature = self.send(&[instruction])?;
+        println!(
+            "Vesting dep for {} from {} cliff {} until {}: {}",
+            beneficiary, start, cliff, end, signature
+        );
+        Ok(())
+    }
+
+    pub async fn withdraw_vested(
+        &self,
+        funder: &str,
+        amount_sol: f64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let funder = parse_pubkey(funder)?;
+        let amount = sol_to_lamports(amount_sol);
+
+        let fee = self.fetch_config()?.withdraw_fee(amount);
+        print_fee_breakdown(amount, fee, "received")?;
+
+        let instruction =
+            instruction::withdraw_vested(&self.program_id, &self.payer.pubkey(), &funder, amount);
+        let signature = self.send(&[instruction])?;
+        println!("Vested withdrawal completed: {}", signature);
+        Ok(())
+    }
+
+    /// Lists the vesting schedules granted to `beneficiary` (the payer by default).
+    pub async fn list_vesting(
+        &self,
+        beneficiary: Option<&str>,
+    ) -> Result<Vec<VestingSchedule>, Box<dyn std::error::Error>> {
+        let beneficiary = match beneficiary {
+            Some(beneficiary) => parse_pubkey(beneficiary)?,
+            None => self.payer.pubkey(),
+        };
+
+        let accounts = self.rpc_client.get_program_accounts_with_config(
+            &self.program_id,
+            RpcProgramAccountsConfig {
+                filters: Some(vec![
+                    RpcFilterType::DataSize(VestingSchedule::LEN as u64),
+                    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(32, beneficiary.as_ref())),
+                ]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    ..RpcAccountInfoConfig::default()
+                },
+                ..RpcProgramAccountsConfig::default()
+            },
+        )?;
+
+        let schedules = accounts
+            .into_iter()
+            .map(|(address, account)| {
+                VestingSchedule::deserialize(&mut account.data.as_slice())
+                    .map_err(|e| format!("Err: decode vesting {}: {}", address, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let now = self
+            .rpc_client
+            .get_block_time(self.rpc_client.get_slot()?)?;
+        for s in &schedules {
+            let vested = s.vested_amount(now);
+            println!(
+                "From {}: total {} SOL, vested {} SOL, withdrawn {} SOL, locked {} SOL (start {} cliff {} end {})",
+                s.funder,
+                lamports_to_sol(s.total),
+                lamports_to_sol(vested),
+                lamports_to_sol(s.withdrawn),
+                lamports_to_sol(s.total - vested),
+                s.start,
+                s.cliff,
+                s.end
+            );
+        }
+        if schedules.is_empty() {
+            println!("No vesting schedules for {}", beneficiary);
+        }
+        Ok(schedules)
+    }
+
+    pub async fn set_limits(
+        &self,
+        max_deposit_sol: f64,
+        max_user_balance_sol: f64,
+        max_tvl_sol: f64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let instruction = instruction::set_limits(
+            &self.program_id,
+            &self.payer.pubkey(),
+            sol_to_lamports(max_deposit_sol),
+            sol_to_lamports(max_user_balance_sol),
+            sol_to_lamports(max_tvl_sol),
+        );
+        let signature = self.send(&[instruction])?;
+        println!(
+            "Limits: deposit {} SOL user balance {} SOL tvl {} SOL: {}",
+            max_deposit_sol, max_user_balance_sol, max_tvl_sol, signature
+        );
+        Ok(())
+    }
+
+    /// Prints the deposit limits and how much `user` (the payer by default)
+    /// can still deposit under each of them.
+    pub async fn get_limits(&self, user: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+        let user = match user {
+            Some(user) => parse_pubkey(user)?,
+            None => self.payer.pubkey(),
+        };
+        let config = self.fetch_config()?;
+        let balance = self.fetch_ledger(&user)?.map_or(0, |ledger| ledger.balance);
+        let tvl = config.tvl();
+
+        let limits = [
+            ("Per deposit", config.max_deposit, None),
+            ("Per user balance", config.max_user_balance, Some(balance)),
+            ("Total value locked", config.max_tvl, Some(tvl)),
+        ];
+        let mut available: Option<u64> = None;
+        for (name, limit, used) in limits {
+            if limit == 0 {
+                println!("{}: unlimited", name);
+                continue;
+            }
+            let headroom = limit.saturating_sub(used.unwrap_or(0));
+            available = Some(available.map_or(headroom, |a| a.min(headroom)));
+            match used {
+                Some(used) => println!(
+                    "{}: {} SOL, used {} SOL, headroom {} SOL",
+                    name,
+                    lamports_to_sol(limit),
+                    lamports_to_sol(used),
+                    lamports_to_sol(headroom)
+                ),
+                None => println!("{}: {} SOL", name, lamports_to_sol(limit)),
+            }
+        }
+        match available {
+            Some(available) => println!(
+                "{} can deposit up to {} SOL",
+                user,
+                lamports_to_sol(available)
+            ),
+            None => println!("{} can deposit without limit", user),
+        }
+        Ok(())
+    }
+
+    pub async fn set_allowlist_enabled(
+        &self,
+        enabled: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let instruction =
+            instruction::set_allowlist_enabled(&self.program_id, &self.payer.pubkey(), enabled);
+        let signature = self.send(&[instruction])?;
+        println!("Allowlist enabled: {}: {}", enabled, signature);
+        Ok(())
+    }
+
+    pub async fn allowlist_add(&self, address: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let address = parse_pubkey(address)?;
+
+        let instruction =
+            instruction::allowlist_add(&self.program_id, &self.payer.pubkey(), &address);
+        let signature = self.send(&[instruction])?;
+        println!("Allowlisted {}: {}", address, signature);
+        Ok(())
+    }
+
+    pub async fn allowlist_remove(&self, address: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let address = parse_pubkey(address)?;
+
+        let instruction =
+            instruction::allowlist_remove(&self.program_id, &self.payer.pubkey(), &address);
+        let signature = self.send(&[instruction])?;
+        println!("Removed from allowlist {}: {}", address, signature);
+        Ok(())
+    }
+
+    /// Allowlists every address in a CSV (first column) or JSON (array of
+    /// strings) file, skipping addresses that already have a marker.
+    pub async fn allowlist_import(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Err: read file '{}': {}", path, e))?;
+        let mut addresses = if path.ends_with(".json") {
+            parse_allowlist_json(&contents)?
+        } else {
+            parse_allowlist_csv(&contents)?
+        };
+        addresses.sort();
+        addresses.dedup();
+
+        let mut pending = Vec::new();
+        for chunk in addresses.chunks(100) {
+            let markers: Vec<Pubkey> = chunk
+                .iter()
+                .map(|address| find_allowlist_address(&self.program_id, address).0)
+                .collect();
+            let accounts = self.rpc_client.get_multiple_accounts(&markers)?;
+            pending.extend(
+                chunk
+                    .iter()
+                    .zip(accounts)
+                    .filter(|(_, account)| account.is_none())
+                    .map(|(address, _)| *address),
+            );
+        }
+        println!(
+            "{} addresses, {} already allowlisted",
+            addresses.len(),
+            addresses.len() - pending.len()
+        );
+
+        let admin = self.payer.pubkey();
+        for batch in pending.chunks(ALLOWLIST_BATCH) {
+            let instructions: Vec<Instruction> = batch
+                .iter()
+                .map(|address| instruction::allowlist_add(&self.program_id, &admin, address))
+                .collect();
+            let signature = self.send(&instructions)?;
+            println!("Allowlisted {} addresses: {}", batch.len(), signature);
+        }
+        Ok(())
+    }
+
+    /// Lets `delegate` withdraw up to `allowance_sol` on the payer's behalf,
+    /// optionally only for the next `expires_in` seconds.
+    pub async fn approve_delegate(
+        &self,
+        delegate: &str,
+        allowance_sol: f64,
+        expires_in: Option<i64>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let delegate = parse_pubkey(delegate)?;
+        let allowance = sol_to_lamports(allowance_sol);
+        let expiry = match expires_in {
+            Some(seconds) => {
+                self.rpc_client
+                    .get_block_time(self.rpc_client.get_slot()?)?
+                    + seconds
+            }
+            None => 0,
+        };
+
+        let instruction = instruction::approve_delegate(
+            &self.program_id,
+            &self.payer.pubkey(),
+            &delegate,
+            allowance,
+            expiry,
+        );
+        let signature = self.send(&[instruction])?;
+        println!(
+            "Delegate {} may withdraw {} SOL: {}",
+            delegate, allowance_sol, signature
+        );
+        Ok(())
+    }
+
+    pub async fn revoke_delegate(&self, delegate: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let delegate = parse_pubkey(delegate)?;
+
+        let instruction =
+            instruction::revoke_delegate(&self.program_id, &self.payer.pubkey(), &delegate);
+        let signature = self.send(&[instruction])?;
+        println!("Delegate {} revoked: {}", delegate, signature);
+        Ok(())
+    }
+
+    /// Withdraws from `owner`'s ledger to `owner` as their delegate.
+    pub async fn withdraw_for(
+        &self,
+        owner: &str,
+        amount_sol: f64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let owner = parse_pubkey(owner)?;
+        let amount = sol_to_lamports(amount_sol);
+
+        let fee = self.fetch_config()?.withdraw_fee(amount);
+        print_fee_breakdown(amount, fee, "received by owner")?;
+
+        let instruction =
+            instruction::withdraw_delegated(&self.program_id, &self.payer.pubkey(), &owner, amount);
+        let signature = self.send(&[instruction])?;
+        println!(
+            "Delegated withdrawal for {} completed: {}",
+            owner, signature
+        );
+        Ok(())
+    }
+
+    /// Lists the delegations granted by `owner` (the payer by default).
+    pub async fn list_delegations(
+        &self,
+        owner: Option<&str>,
+    ) -> Result<Vec<Delegation>, Box<dyn std::error::Error>> {
+        let owner = match owner {
+            Some(owner) => parse_pubkey(owner)?,
+            None => self.payer.pubkey(),
+        };
+        let delegations = self.fetch_delegations(&owner)?;
+
+        let now = self
+            .rpc_client
+            .get_block_time(self.rpc_client.get_slot()?)?;
+        for d in &delegations {
+            let expiry = match d.expiry {
+                0 => "never expires".to_string(),
+                _ if d.is_expired(now) => format!("expired at {}", d.expiry),
+                _ => format!("expires at {}", d.expiry),
+            };
+            println!(
+                "Delegate {}: allowance {} SOL, {}",
+                d.delegate,
+                lamports_to_sol(d.allowance),
+                expiry
+            );
+        }
+        if delegations.is_empty() {
+            println!("No delegations for {}", owner);
+        }
+        Ok(delegations)
+    }
+
+    fn fetch_delegations(
+        &self,
+        owner: &Pubkey,
+    ) -> Result<Vec<Delegation>, Box<dyn std::error::Error>> {
+        let accounts = self.rpc_client.get_program_accounts_with_config(
+            &self.program_id,
+            RpcProgramAccountsConfig {
+                filters: Some(vec![
+                    RpcFilterType::DataSize(Delegation::LEN as u64),
+                    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, owner.as_ref())),
+                ]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    ..RpcAccountInfoConfig::default()
+                },
+                ..RpcProgramAccountsConfig::default()
+            },
+        )?;
+
+        Ok(accounts
+            .into_iter()
+            .map(|(address, account)| {
+                Delegation::deserialize(&mut account.data.as_slice())
+                    .map_err(|e| format!("Err: decode delegation {}: {}", address, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Token ledgers of `user`, one per deposited mint.
+    fn fetch_token_ledgers(
+        &self,
+        user: &Pubkey,
+    ) -> Result<Vec<TokenDepositAccount>, Box<dyn std::error::Error>> {
+        let accounts = self.rpc_client.get_program_accounts_with_config(
+            &self.program_id,
+            RpcProgramAccountsConfig {
+                filters: Some(vec![
+                    RpcFilterType::DataSize(TokenDepositAccount::LEN as u64),
+                    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, user.as_ref())),
+                ]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    ..RpcAccountInfoConfig::default()
+                },
+                ..RpcProgramAccountsConfig::default()
+            },
+        )?;
 
-        let deposit_pubkey = Pubkey::from_str(deposit_account).map_err(|e| {
-            format!(
-                "Err: parsing pb key {}: {}",
-                deposit_account, e
+        Ok(accounts
+            .into_iter()
+            .map(|(address, account)| {
+                TokenDepositAccount::deserialize(&mut account.data.as_slice())
+                    .map_err(|e| format!("Err: decode token ledger {}: {}", address, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?)
+    }
+
+    /// Withdraws the payer's whole balance and closes their ledger, empty
+    /// token ledgers and delegations, reclaiming the rent.
+    pub async fn close(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let user = self.payer.pubkey();
+        let ledger = self
+            .fetch_ledger(&user)?
+            .ok_or_else(|| format!("Err: no ledger for {}", user))?;
+        if ledger.pending_withdrawals > 0 {
+            return Err(format!(
+                "Err: {} withdrawals pending, claim or cancel them first",
+                ledger.pending_withdrawals
             )
-        })?;
+            .into());
+        }
+
+        let token_ledgers = self.fetch_token_ledgers(&user)?;
+        if let Some(held) = token_ledgers.iter().find(|t| t.balance > 0) {
+            return Err(format!(
+                "Err: withdraw {} tokens of mint {} first",
+                held.balance, held.mint
+            )
+            .into());
+        }
+        let mints: Vec<Pubkey> = token_ledgers.iter().map(|t| t.mint).collect();
+        let delegates: Vec<Pubkey> = self
+            .fetch_delegations(&user)?
+            .iter()
+            .map(|d| d.delegate)
+            .collect();
+
+        let fee = self
+            .fetch_config()?
+            .withdraw_fee(ledger.balance)
+            .min(ledger.balance);
+        print_fee_breakdown(ledger.balance, fee, "received")?;
+
+        let instruction = instruction::close_account(&self.program_id, &user, &mints, &delegates);
+        let signature = self.send(&[instruction])?;
+        println!(
+            "Closed ledger, {} token ledgers and {} delegations: {}",
+            mints.len(),
+            delegates.len(),
+            signature
+        );
+        Ok(())
+    }
+
+    /// Escrows created by `depositor` that are still open.
+    fn fetch_escrows(&self, depositor: &Pubkey) -> Result<Vec<Escrow>, Box<dyn std::error::Error>> {
+        let accounts = self.rpc_client.get_program_accounts_with_config(
+            &self.program_id,
+            RpcProgramAccountsConfig {
+                filters: Some(vec![
+                    RpcFilterType::DataSize(Escrow::LEN as u64),
+                    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, depositor.as_ref())),
+                ]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    ..RpcAccountInfoConfig::default()
+                },
+                ..RpcProgramAccountsConfig::default()
+            },
+        )?;
 
-        let mut instruction_data = vec![1u8];
-        instruction_data.extend_from_slice(&amount.to_le_bytes());
+        Ok(accounts
+            .into_iter()
+            .map(|(address, account)| {
+                Escrow::deserialize(&mut account.data.as_slice())
+                    .map_err(|e| format!("Err: decode escrow {}: {}", address, e))
+            })
+            .collect::<Result<Vec<_>, _>>()?)
+    }
 
-        let instruction = Instruction::new(
-            self.program_id,
-            &instruction_data,
-            vec![
-                AccountMeta::new(self.payer.pubkey(), true),
-                AccountMeta::new(deposit_pubkey, false),
-                AccountMeta::new_readonly(system_program::id(), false),
-            ],
+    pub async fn escrow_create(
+        &self,
+        counterparty: &str,
+        amount_sol: f64,
+        timeout: i64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let counterparty = parse_pubkey(counterparty)?;
+        let amount = sol_to_lamports(amount_sol);
+        let depositor = self.payer.pubkey();
+        let id = self
+            .fetch_escrows(&depositor)?
+            .iter()
+            .map(|escrow| escrow.id + 1)
+            .max()
+            .unwrap_or(0);
+
+        let fee = self.fetch_config()?.deposit_fee(amount);
+        print_fee_breakdown(amount, fee, "locked")?;
+
+        let instruction = instruction::escrow_create(
+            &self.program_id,
+            &depositor,
+            id,
+            amount,
+            &counterparty,
+            timeout,
+        );
+        let signature = self.send(&[instruction])?;
+        println!(
+            "Escrow {} for {} refundable in {}s: {}",
+            id, counterparty, timeout, signature
         );
+        Ok(())
+    }
+
+    pub async fn escrow_release(&self, id: u64) -> Result<(), Box<dyn std::error::Error>> {
+        let instruction = instruction::escrow_release(&self.program_id, &self.payer.pubkey(), id);
+        let signature = self.send(&[instruction])?;
+        println!("Escrow {} released: {}", id, signature);
+        Ok(())
+    }
+
+    pub async fn escrow_claim(
+        &self,
+        depositor: &str,
+        id: u64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let depositor = parse_pubkey(depositor)?;
+        let address = find_escrow_address(&self.program_id, &depositor, id).0;
+        let data = self
+            .rpc_client
+            .get_account_data(&address)
+            .map_err(|e| format!("Err: no escrow #{} from {}: {}", id, depositor, e))?;
+        let escrow = Escrow::deserialize(&mut data.as_slice())
+            .map_err(|e| format!("Err: decode escrow {}: {}", address, e))?;
+        let fee = self.fetch_config()?.withdraw_fee(escrow.amount);
+        print_fee_breakdown(escrow.amount, fee, "received")?;
+
+        let instruction =
+            instruction::escrow_claim(&self.program_id, &self.payer.pubkey(), &depositor, id);
+        let signature = self.send(&[instruction])?;
+        println!("Escrow {} claimed: {}", id, signature);
+        Ok(())
+    }
+
+    pub async fn escrow_refund(&self, id: u64) -> Result<(), Box<dyn std::error::Error>> {
+        let instruction = instruction::escrow_refund(&self.program_id, &self.payer.pubkey(), id);
+        let signature = self.send(&[instruction])?;
+        println!("Escrow {} refunded: {}", id, signature);
+        Ok(())
+    }
+
+    /// Decimals of `mint`, used to convert UI amounts to base units.
+    fn mint_decimals(&self, mint: &Pubkey) -> Result<u8, Box<dyn std::error::Error>> {
+        let data = self
+            .rpc_client
+            .get_account_data(mint)
+            .map_err(|e| format!("Err: fetch mint {}: {}", mint, e))?;
+        Ok(Mint::unpack(&data)?.decimals)
+    }
+
+    pub async fn deposit_token(
+        &self,
+        mint: &str,
+        ui_amount: f64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mint = parse_pubkey(mint)?;
+        let decimals = self.mint_decimals(&mint)?;
+        let amount = spl_token::ui_amount_to_amount(ui_amount, decimals);
+        let user = self.payer.pubkey();
+        let user_token = get_associated_token_address(&user, &mint);
 
+        let instruction =
+            instruction::deposit_token(&self.program_id, &user, &user_token, &mint, amount);
+        let signature = self.send(&[instruction])?;
+        println!(
+            "Token dep {} ({} base units): {}",
+            ui_amount, amount, signature
+        );
+        Ok(())
+    }
+
+    pub async fn withdraw_token(
+        &self,
+        mint: &str,
+        ui_amount: f64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mint = parse_pubkey(mint)?;
+        let decimals = self.mint_decimals(&mint)?;
+        let amount = spl_token::ui_amount_to_amount(ui_amount, decimals);
+        let user = self.payer.pubkey();
+        let user_token = get_associated_token_address(&user, &mint);
+
+        let instructions = [
+            create_associated_token_account_idempotent(&user, &user, &mint, &spl_token::id()),
+            instruction::withdraw_token(&self.program_id, &user, &user_token, &mint, amount),
+        ];
+        let signature = self.send(&instructions)?;
+        println!(
+            "Token withdrawal {} ({} base units): {}",
+            ui_amount, amount, signature
+        );
+        Ok(())
+    }
+
+    pub async fn get_token_balance(
+        &self,
+        mint: &str,
+        user: Option<&str>,
+    ) -> Result<f64, Box<dyn std::error::Error>> {
+        let mint = parse_pubkey(mint)?;
+        let user = match user {
+            Some(user) => parse_pubkey(user)?,
+            None => self.payer.pubkey(),
+        };
+        let decimals = self.mint_decimals(&mint)?;
+        let ledger = find_token_ledger_address(&self.program_id, &user, &mint).0;
+
+        let amount = match self.rpc_client.get_account_data(&ledger) {
+            Ok(data) => {
+                TokenDepositAccount::deserialize(&mut data.as_slice())
+                    .map_err(|e| format!("Err: decode token ledger {}: {}", ledger, e))?
+                    .balance
+            }
+            Err(_) => 0,
+        };
+        let ui_amount = spl_token::amount_to_ui_amount(amount, decimals);
+
+        println!(
+            "Token balance {} of {}: {} ({} base units)",
+            user, mint, ui_amount, amount
+        );
+        Ok(ui_amount)
+    }
+
+    pub async fn create_share_mint(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let instruction = instruction::create_share_mint(&self.program_id, &self.payer.pubkey());
+        let signature = self.send(&[instruction])?;
+        println!(
+            "Share mint created {}: {}",
+            find_share_mint_address(&self.program_id).0,
+            signature
+        );
+        Ok(())
+    }
+
+    /// Prints the receipt shares held by `user` (the payer by default) and
+    /// what they currently redeem for.
+    pub async fn get_shares(&self, user: Option<&str>) -> Result<u64, Box<dyn std::error::Error>> {
+        let user = match user {
+            Some(user) => parse_pubkey(user)?,
+            None => self.payer.pubkey(),
+        };
+        let config = self.fetch_config()?;
+        if !config.shares_enabled {
+            println!("Receipt shares are not enabled");
+            return Ok(0);
+        }
+
+        let share_mint = find_share_mint_address(&self.program_id).0;
+        let supply = Mint::unpack(&self.rpc_client.get_account_data(&share_mint)?)?.supply;
+        let share_account = get_share_account_address(&self.program_id, &user);
+        let held = match self.rpc_client.get_account_data(&share_account) {
+            Ok(data) => spl_token::state::Account::unpack(&data)?.amount,
+            Err(_) => 0,
+        };
+        println!("Share mint: {}", share_mint);
+        println!(
+            "Shares of {}: {} (value {} SOL)",
+            user,
+            lamports_to_sol(held),
+            lamports_to_sol(config.share_value(held, supply))
+        );
+        println!(
+            "Exchange rate: {} SOL per share ({} SOL backing {} shares)",
+            config.share_value(LAMPORTS_PER_SOL, supply) as f64 / LAMPORTS_PER_SOL as f64,
+            lamports_to_sol(config.share_pool),
+            lamports_to_sol(supply)
+        );
+        Ok(held)
+    }
+
+    /// Burns `shares` receipt shares held by the payer for their current
+    /// value in lamports.
+    pub async fn redeem_shares(&self, shares: f64) -> Result<(), Box<dyn std::error::Error>> {
+        let shares = sol_to_lamports(shares);
+        let config = self.fetch_config()?;
+        let share_mint = find_share_mint_address(&self.program_id).0;
+        let supply = Mint::unpack(&self.rpc_client.get_account_data(&share_mint)?)?.supply;
+        let value = config.share_value(shares, supply);
+
+        let fee = config.withdraw_fee(value);
+        print_fee_breakdown(value, fee, "received")?;
+
+        let instruction =
+            instruction::redeem_shares(&self.program_id, &self.payer.pubkey(), shares);
+        let signature = self.send(&[instruction])?;
+        println!(
+            "Redeemed {} shares for {} SOL: {}",
+            lamports_to_sol(shares),
+            lamports_to_sol(value),
+            signature
+        );
+        Ok(())
+    }
+
+    pub async fn distribute_rewards(
+        &self,
+        amount_sol: f64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let amount = sol_to_lamports(amount_sol);
+        let total = self.fetch_config()?.total_deposits;
+
+        let instruction =
+            instruction::distribute_rewards(&self.program_id, &self.payer.pubkey(), amount);
+        let signature = self.send(&[instruction])?;
+        println!(
+            "Distributed {} SOL over {} SOL deposited: {}",
+            amount_sol,
+            lamports_to_sol(total),
+            signature
+        );
+        Ok(())
+    }
+
+    pub async fn claim_rewards(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let pending = self.get_rewards(None).await?;
+        if pending == 0 {
+            return Err("Err: no rewards to claim".into());
+        }
+
+        let instruction = instruction::claim_rewards(&self.program_id, &self.payer.pubkey());
+        let signature = self.send(&[instruction])?;
+        println!("Rewards claimed: {}", signature);
+        Ok(())
+    }
+
+    /// Prints and returns the rewards `user` (the payer by default) can claim.
+    pub async fn get_rewards(&self, user: Option<&str>) -> Result<u64, Box<dyn std::error::Error>> {
+        let user = match user {
+            Some(user) => parse_pubkey(user)?,
+            None => self.payer.pubkey(),
+        };
+        let config = self.fetch_config()?;
+        let pending = self
+            .fetch_ledger(&user)?
+            .map(|ledger| ledger.pending_rewards(config.reward_index))
+            .unwrap_or(0);
+
+        println!(
+            "Pending rewards of {}: {} SOL",
+            user,
+            lamports_to_sol(pending)
+        );
+        Ok(pending)
+    }
+
+    /// Prints the events the program logged in the transaction `signature`.
+    pub async fn get_events(&self, signature: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let signature = Signature::from_str(signature)
+            .map_err(|e| format!("Err: parsing signature {}: {}", signature, e))?;
+        let transaction = self
+            .rpc_client
+            .get_transaction(&signature, UiTransactionEncoding::Json)?;
+        let logs: Option<Vec<String>> = transaction
+            .transaction
+            .meta
+            .and_then(|meta| meta.log_messages.into());
+        let logs = logs.ok_or("Err: transaction has no log messages")?;
+
+        let events = parse_events(&self.program_id, &logs);
+        if events.is_empty() {
+            println!("No events in {}", signature);
+        }
+        for event in events {
+            println!(
+                "{:?} at slot {}: user {} amount {} balance {}",
+                event.kind, event.slot, event.user, event.amount, event.balance
+            );
+        }
+        Ok(())
+    }
+
+    /// Signs and sends `instructions`, turning program error codes into [`DepositError`].
+    fn send(&self, instructions: &[Instruction]) -> Result<Signature, Box<dyn std::error::Error>> {
         let blockhash = self.rpc_client.get_latest_blockhash()?;
         let transaction = Transaction::new_signed_with_payer(
-            &[instruction],
+            instructions,
             Some(&self.payer.pubkey()),
             &[&self.payer],
             blockhash,
         );
 
-        let signature = self.rpc_client.send_and_confirm_transaction(&transaction)?;
-        println!("Withdrawal completed: {}", signature);
-        Ok(())
+        self.rpc_client
+            .send_and_confirm_transaction(&transaction)
+            .map_err(decode_error)
     }
 
-    pub async fn get_balance(
-        &self,
-        deposit_account: &str,
-    ) -> Result<f64, Box<dyn std::error::Error>> {
-        println!("Parsing deposit account: {}", deposit_account);
-        let deposit_pubkey = Pubkey::from_str(deposit_account)
-            .map_err(|e| format!("Err: parsing pb key {}: {}", deposit_account, e))?;
+    pub async fn get_balance(&self, user: Option<&str>) -> Result<f64, Box<dyn std::error::Error>> {
+        let user_pubkey = match user {
+            Some(user) => parse_pubkey(user)?,
+            None => self.payer.pubkey(),
+        };
+        let ledger = self.ledger_address(&user_pubkey);
+        println!("Ledger: {} (user {})", ledger, user_pubkey);
 
-        let balance_lamports = self.rpc_client.get_balance(&deposit_pubkey)?;
-        let balance_sol = balance_lamports as f64 / 1_000_000_000.0;
+        let balance_lamports = match self.fetch_ledger(&user_pubkey)? {
+            Some(deposit_account) => deposit_account.balance,
+            None => {
+                println!("No ledger yet for {}", user_pubkey);
+                0
+            }
+        };
+        let balance_sol = lamports_to_sol(balance_lamports);
 
         println!("Balance: {} SOL ({} lapms)", balance_sol, balance_lamports);
+
+        let config = self.fetch_config()?;
+        if config.paused {
+            println!(
+                "Program paused, withdrawals {}",
+                if config.allow_withdrawals_when_paused {
+                    "allowed"
+                } else {
+                    "blocked"
+                }
+            );
+        }
+        if config.fee_collector == user_pubkey {
+            println!(
+                "Accrued fees: {} SOL ({} lamports)",
+                lamports_to_sol(config.accrued_fees),
+                config.accrued_fees
+            );
+        }
         Ok(balance_sol)
     }
 }
 
+fn parse_pubkey(value: &str) -> Result<Pubkey, Box<dyn std::error::Error>> {
+    Ok(Pubkey::from_str(value).map_err(|e| format!("Err: parsing pb key {}: {}", value, e))?)
+}
+
+/// Prints how `amount` splits into the fee and what the user ends up with.
+fn print_fee_breakdown(
+    amount: u64,
+    fee: u64,
+    net_label: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let net = amount
+        .checked_sub(fee)
+        .ok_or(DepositError::AmountBelowFee)?;
+    println!(
+        "Amount: {} SOL, fee: {} SOL, {}: {} SOL",
+        lamports_to_sol(amount),
+        lamports_to_sol(fee),
+        net_label,
+        lamports_to_sol(net)
+    );
+    Ok(())
+}
+
+/// First column of every CSV row; a header row that is not an address is skipped.
+fn parse_allowlist_csv(contents: &str) -> Result<Vec<Pubkey>, Box<dyn std::error::Error>> {
+    let mut addresses = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let field = line
+            .split(',')
+            .next()
+            .unwrap_or("")
+            .trim()
+            .trim_matches('"');
+        if field.is_empty() || field.starts_with('#') {
+            continue;
+        }
+        match Pubkey::from_str(field) {
+            Ok(address) => addresses.push(address),
+            Err(_) if index == 0 => continue,
+            Err(e) => return Err(format!("Err: line {}: '{}': {}", index + 1, field, e).into()),
+        }
+    }
+    Ok(addresses)
+}
+
+fn parse_allowlist_json(contents: &str) -> Result<Vec<Pubkey>, Box<dyn std::error::Error>> {
+    let entries: Vec<String> = serde_json::from_str(contents)
+        .map_err(|e| format!("Err: parsing JSON allowlist: {}", e))?;
+    entries.iter().map(|entry| parse_pubkey(entry)).collect()
+}
+
+fn decode_error(error: ClientError) -> Box<dyn std::error::Error> {
+    if let Some(TransactionError::InstructionError(_, InstructionError::Custom(code))) =
+        error.get_transaction_error()
+    {
+        if let Some(deposit_error) = DepositError::from_code(code) {
+            return Box::new(deposit_error);
+        }
+    }
+    error.into()
+}
+
+/// Allowlist markers created per transaction by `allowlist-import`.
+const ALLOWLIST_BATCH: usize = 10;
+
+/// Proposals expire after a day unless `propose` is given an expiry.
+const DEFAULT_PROPOSAL_EXPIRY: i64 = 24 * 60 * 60;
+
+const COMMANDS: &[&str] = &[
+    "deposit <amount>",
+    "withdraw <amount>",
+    "balance [user]",
+    "initialize",
+    "set-authority <new_authority>",
+    "accept-authority",
+    "pause [--block-withdrawals]",
+    "unpause",
+    "set-withdraw-delay <seconds>",
+    "request-withdraw <amount>",
+    "withdrawals [user]",
+    "claim <id>",
+    "cancel-withdraw <id>",
+    "deposit-token <mint> <amount>",
+    "withdraw-token <mint> <amount>",
+    "token-balance <mint> [user]",
+    "set-fees <deposit_bps> <withdraw_bps> <min_fee> <collector>",
+    "collect-fees",
+    "set-multisig <threshold> <signer>...",
+    "propose <owner> <amount> <recipient> [expires_in_seconds]",
+    "approve <owner> <id>",
+    "execute <owner> <id>",
+    "cancel-proposal <owner> <id>",
+    "list-proposals [owner]",
+    "deposit-vested <beneficiary> <amount> <start> <cliff> <end>",
+    "withdraw-vested <funder> <amount>",
+    "vesting [beneficiary]",
+    "escrow-create <counterparty> <amount> <timeout_seconds>",
+    "escrow-release <id>",
+    "escrow-claim <depositor> <id>",
+    "escrow-refund <id>",
+    "set-limits <max_deposit> <max_user_balance> <max_tvl>",
+    "limits [user]",
+    "allowlist-enable",
+    "allowlist-disable",
+    "allowlist-add <address>",
+    "allowlist-remove <address>",
+    "allowlist-import <file.csv|file.json>",
+    "approve-delegate <delegate> <allowance> [expires_in_seconds]",
+    "revoke-delegate <delegate>",
+    "delegations [owner]",
+    "withdraw-for <owner> <amount>",
+    "close",
+    "create-share-mint",
+    "shares [user]",
+    "redeem <shares>",
+    "distribute-rewards <amount>",
+    "claim-rewards",
+    "rewards [user]",
+    "events <signature>",
+];
+
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() {
+    if let Err(e) = run().await {
+        println!("Err: {}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run() -> Result<(), Box<dyn std::error::Error>> {
     let args: Vec<String> = std::env::args().collect();
 
-    if args.len() < 6 {
-        println!(
-            "  {} <rpc_url> <program_id> <keypair_path> deposit <deposit_account> <amount>",
-            args[0]
-        );
-        println!(
-            "  {} <rpc_url> <program_id> <keypair_path> withdraw <deposit_account> <amount>",
-            args[0]
-        );
-        println!(
-            "  {} <rpc_url> <program_id> <keypair_path> balance <deposit_account>",
-            args[0]
-        );
+    if args.len() < 5 {
+        for command in COMMANDS {
+            println!(
+                "  {} <rpc_url> <program_id> <keypair_path> {}",
+                args[0], command
+            );
+        }
         return Ok(());
     }
 
@@ -176,18 +1517,270 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     match args[4].as_str() {
         "deposit" => {
-            let amount: f64 = args[6].parse()?;
-            client.deposit(&args[5], amount).await?;
+            let amount: f64 = args.get(5).ok_or("Err: missing amount")?.parse()?;
+            client.deposit(amount).await?;
         }
         "withdraw" => {
-            let amount: f64 = args[6].parse()?;
-            client.withdraw(&args[5], amount).await?;
+            let amount: f64 = args.get(5).ok_or("Err: missing amount")?.parse()?;
+            client.withdraw(amount).await?;
         }
         "balance" => {
-            client.get_balance(&args[5]).await?;
+            client.get_balance(args.get(5).map(String::as_str)).await?;
+        }
+        "initialize" => {
+            client.initialize().await?;
+        }
+        "set-authority" => {
+            let new_authority = args.get(5).ok_or("Err: missing new authority")?;
+            client.set_authority(new_authority).await?;
+        }
+        "accept-authority" => {
+            client.accept_authority().await?;
+        }
+        "pause" => {
+            let block_withdrawals = args.get(5).map(String::as_str) == Some("--block-withdrawals");
+            client.set_paused(true, !block_withdrawals).await?;
+        }
+        "unpause" => {
+            client.set_paused(false, true).await?;
+        }
+        "set-withdraw-delay" => {
+            let seconds: i64 = args.get(5).ok_or("Err: missing seconds")?.parse()?;
+            client.set_withdraw_delay(seconds).await?;
+        }
+        "request-withdraw" => {
+            let amount: f64 = args.get(5).ok_or("Err: missing amount")?.parse()?;
+            client.request_withdraw(amount).await?;
+        }
+        "withdrawals" => {
+            client
+                .list_withdrawals(args.get(5).map(String::as_str))
+                .await?;
+        }
+        "claim" => {
+            let id: u64 = args.get(5).ok_or("Err: missing id")?.parse()?;
+            client.claim_withdraw(id).await?;
+        }
+        "cancel-withdraw" => {
+            let id: u64 = args.get(5).ok_or("Err: missing id")?.parse()?;
+            client.cancel_withdraw(id).await?;
+        }
+        "deposit-token" => {
+            let mint = args.get(5).ok_or("Err: missing mint")?;
+            let amount: f64 = args.get(6).ok_or("Err: missing amount")?.parse()?;
+            client.deposit_token(mint, amount).await?;
+        }
+        "withdraw-token" => {
+            let mint = args.get(5).ok_or("Err: missing mint")?;
+            let amount: f64 = args.get(6).ok_or("Err: missing amount")?.parse()?;
+            client.withdraw_token(mint, amount).await?;
+        }
+        "token-balance" => {
+            let mint = args.get(5).ok_or("Err: missing mint")?;
+            client
+                .get_token_balance(mint, args.get(6).map(String::as_str))
+                .await?;
+        }
+        "set-fees" => {
+            let deposit_fee_bps: u16 = args.get(5).ok_or("Err: missing deposit bps")?.parse()?;
+            let withdraw_fee_bps: u16 = args.get(6).ok_or("Err: missing withdraw bps")?.parse()?;
+            let min_fee: f64 = args.get(7).ok_or("Err: missing min fee")?.parse()?;
+            let fee_collector = args.get(8).ok_or("Err: missing fee collector")?;
+            client
+                .set_fees(deposit_fee_bps, withdraw_fee_bps, min_fee, fee_collector)
+                .await?;
+        }
+        "collect-fees" => {
+            client.collect_fees().await?;
+        }
+        "set-multisig" => {
+            let threshold: u8 = args.get(5).ok_or("Err: missing threshold")?.parse()?;
+            client.set_multisig(threshold, &args[6..]).await?;
+        }
+        "propose" => {
+            let owner = args.get(5).ok_or("Err: missing owner")?;
+            let amount: f64 = args.get(6).ok_or("Err: missing amount")?.parse()?;
+            let recipient = args.get(7).ok_or("Err: missing recipient")?;
+            let expires_in: i64 = match args.get(8) {
+                Some(seconds) => seconds.parse()?,
+                None => DEFAULT_PROPOSAL_EXPIRY,
+            };
+            client.propose(owner, amount, recipient, expires_in).await?;
+        }
+        "approve" => {
+            let owner = args.get(5).ok_or("Err: missing owner")?;
+            let id: u64 = args.get(6).ok_or("Err: missing id")?.parse()?;
+            client.approve(owner, id).await?;
+        }
+        "execute" => {
+            let owner = args.get(5).ok_or("Err: missing owner")?;
+            let id: u64 = args.get(6).ok_or("Err: missing id")?.parse()?;
+            client.execute(owner, id).await?;
+        }
+        "cancel-proposal" => {
+            let owner = args.get(5).ok_or("Err: missing owner")?;
+            let id: u64 = args.get(6).ok_or("Err: missing id")?.parse()?;
+            client.cancel_proposal(owner, id).await?;
+        }
+        "list-proposals" => {
+            client
+                .list_proposals(args.get(5).map(String::as_str))
+                .await?;
+        }
+        "deposit-vested" => {
+            let beneficiary = args.get(5).ok_or("Err: missing beneficiary")?;
+            let amount: f64 = args.get(6).ok_or("Err: missing amount")?.parse()?;
+            let start: i64 = args.get(7).ok_or("Err: missing start")?.parse()?;
+            let cliff: i64 = args.get(8).ok_or("Err: missing cliff")?.parse()?;
+            let end: i64 = args.get(9).ok_or("Err: missing end")?.parse()?;
+            client
+                .deposit_vested(beneficiary, amount, start, cliff, end)
+                .await?;
+        }
+        "withdraw-vested" => {
+            let funder = args.get(5).ok_or("Err: missing funder")?;
+            let amount: f64 = args.get(6).ok_or("Err: missing amount")?.parse()?;
+            client.withdraw_vested(funder, amount).await?;
+        }
+        "vesting" => {
+            client.list_vesting(args.get(5).map(String::as_str)).await?;
+        }
+        "escrow-create" => {
+            let counterparty = args.get(5).ok_or("Err: missing counterparty")?;
+            let amount: f64 = args.get(6).ok_or("Err: missing amount")?.parse()?;
+            let timeout: i64 = args.get(7).ok_or("Err: missing timeout")?.parse()?;
+            client.escrow_create(counterparty, amount, timeout).await?;
+        }
+        "escrow-release" => {
+            let id: u64 = args.get(5).ok_or("Err: missing id")?.parse()?;
+            client.escrow_release(id).await?;
+        }
+        "escrow-claim" => {
+            let depositor = args.get(5).ok_or("Err: missing depositor")?;
+            let id: u64 = args.get(6).ok_or("Err: missing id")?.parse()?;
+            client.escrow_claim(depositor, id).await?;
+        }
+        "escrow-refund" => {
+            let id: u64 = args.get(5).ok_or("Err: missing id")?.parse()?;
+            client.escrow_refund(id).await?;
+        }
+        "set-limits" => {
+            let max_deposit: f64 = args.get(5).ok_or("Err: missing max deposit")?.parse()?;
+            let max_user_balance: f64 = args
+                .get(6)
+                .ok_or("Err: missing max user balance")?
+                .parse()?;
+            let max_tvl: f64 = args.get(7).ok_or("Err: missing max tvl")?.parse()?;
+            client
+                .set_limits(max_deposit, max_user_balance, max_tvl)
+                .await?;
+        }
+        "limits" => {
+            client.get_limits(args.get(5).map(String::as_str)).await?;
+        }
+        "allowlist-enable" => {
+            client.set_allowlist_enabled(true).await?;
+        }
+        "allowlist-disable" => {
+            client.set_allowlist_enabled(false).await?;
+        }
+        "allowlist-add" => {
+            let address = args.get(5).ok_or("Err: missing address")?;
+            client.allowlist_add(address).await?;
+        }
+        "allowlist-remove" => {
+            let address = args.get(5).ok_or("Err: missing address")?;
+            client.allowlist_remove(address).await?;
+        }
+        "allowlist-import" => {
+            let path = args.get(5).ok_or("Err: missing file")?;
+            client.allowlist_import(path).await?;
+        }
+        "approve-delegate" => {
+            let delegate = args.get(5).ok_or("Err: missing delegate")?;
+            let allowance: f64 = args.get(6).ok_or("Err: missing allowance")?.parse()?;
+            let expires_in = args.get(7).map(|s| s.parse::<i64>()).transpose()?;
+            client
+                .approve_delegate(delegate, allowance, expires_in)
+                .await?;
+        }
+        "revoke-delegate" => {
+            let delegate = args.get(5).ok_or("Err: missing delegate")?;
+            client.revoke_delegate(delegate).await?;
+        }
+        "delegations" => {
+            client
+                .list_delegations(args.get(5).map(String::as_str))
+                .await?;
+        }
+        "withdraw-for" => {
+            let owner = args.get(5).ok_or("Err: missing owner")?;
+            let amount: f64 = args.get(6).ok_or("Err: missing amount")?.parse()?;
+            client.withdraw_for(owner, amount).await?;
+        }
+        "close" => {
+            client.close().await?;
+        }
+        "create-share-mint" => {
+            client.create_share_mint().await?;
+        }
+        "shares" => {
+            client.get_shares(args.get(5).map(String::as_str)).await?;
+        }
+        "redeem" => {
+            let shares: f64 = args.get(5).ok_or("Err: missing shares")?.parse()?;
+            client.redeem_shares(shares).await?;
+        }
+        "distribute-rewards" => {
+            let amount: f64 = args.get(5).ok_or("Err: missing amount")?.parse()?;
+            client.distribute_rewards(amount).await?;
+        }
+        "claim-rewards" => {
+            client.claim_rewards().await?;
+        }
+        "rewards" => {
+            client.get_rewards(args.get(5).map(String::as_str)).await?;
+        }
+        "events" => {
+            let signature = args.get(5).ok_or("Err: missing signature")?;
+            client.get_events(signature).await?;
         }
         _ => println!("Err: args 404 check client-main: {}", args[4]),
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_takes_the_first_column_and_skips_a_header() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let contents = format!("address,label\n{},alice\n\n# comment\n\"{}\",bob\n", a, b);
+        assert_eq!(parse_allowlist_csv(&contents).unwrap(), vec![a, b]);
+        assert_eq!(parse_allowlist_csv(&a.to_string()).unwrap(), vec![a]);
+    }
+
+    #[test]
+    fn csv_reports_the_line_of_a_bad_address() {
+        let contents = format!("{}\nnot-an-address\n", Pubkey::new_unique());
+        let err = parse_allowlist_csv(&contents).unwrap_err().to_string();
+        assert!(err.starts_with("Err: line 2: 'not-an-address'"), "{}", err);
+    }
+
+    #[test]
+    fn json_takes_an_array_of_addresses() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let contents = format!("[\"{}\", \"{}\"]", a, b);
+        assert_eq!(parse_allowlist_json(&contents).unwrap(), vec![a, b]);
+        assert!(parse_allowlist_json("[]").unwrap().is_empty());
+    }
+
+    #[test]
+    fn json_rejects_bad_documents_and_addresses() {
+        assert!(parse_allowlist_json("{\"address\": 1}").is_err());
+        assert!(parse_allowlist_json("[\"not-an-address\"]").is_err());
+    }
+}
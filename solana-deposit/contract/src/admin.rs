@@ -0,0 +1,200 @@
+use solana_deposit_interface::{
+    error::DepositError, event::EventKind, find_program_data_address, state::Config, CONFIG_SEED,
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
+};
+
+use crate::{
+    events::emit,
+    utils::{check_config, check_vault, create_pda_account, load_config, store, transfer_to_vault},
+};
+
+pub fn initialize(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let admin = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let vault = next_account_info(accounts_iter)?;
+    let program_data = next_account_info(accounts_iter)?;
+
+    if !admin.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    check_upgrade_authority(program_id, admin, program_data)?;
+    check_vault(program_id, vault)?;
+    let bump = check_config(program_id, config)?;
+    if !config.data_is_empty() {
+        return Err(DepositError::AlreadyInitialized.into());
+    }
+
+    create_pda_account(
+        program_id,
+        admin,
+        config,
+        system_program,
+        Config::LEN,
+        &[CONFIG_SEED, &[bump]],
+    )?;
+
+    // Payouts never dip into this reserve, so the vault stays rent-exempt.
+    let reserve = Rent::get()?
+        .minimum_balance(0)
+        .saturating_sub(vault.lamports());
+    if reserve > 0 {
+        transfer_to_vault(admin, vault, system_program, reserve)?;
+    }
+
+    let state = Config {
+        admin: *admin.key,
+        ..Config::default()
+    };
+    store(&state, config)?;
+
+    msg!("Initialized, admin: {}", admin.key);
+    emit(EventKind::Initialize, admin.key, 0, 0)
+}
+
+pub fn set_authority(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_authority: Pubkey,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let admin = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+
+    let mut state = load_admin_config(program_id, admin, config)?;
+    state.pending_admin = new_authority;
+    store(&state, config)?;
+
+    msg!("Pending admin: {}", new_authority);
+    emit(EventKind::SetAuthority, &new_authority, 0, 0)
+}
+
+pub fn accept_authority(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let new_admin = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+
+    if !new_admin.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut state = load_config(program_id, config)?;
+    if state.pending_admin == Pubkey::default() || state.pending_admin != *new_admin.key {
+        return Err(DepositError::Unauthorized.into());
+    }
+
+    state.admin = *new_admin.key;
+    state.pending_admin = Pubkey::default();
+    store(&state, config)?;
+
+    msg!("Admin: {}", new_admin.key);
+    emit(EventKind::AcceptAuthority, new_admin.key, 0, 0)
+}
+
+pub fn set_paused(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    paused: bool,
+    allow_withdrawals: bool,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let admin = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+
+    let mut state = load_admin_config(program_id, admin, config)?;
+    state.paused = paused;
+    state.allow_withdrawals_when_paused = allow_withdrawals;
+    store(&state, config)?;
+
+    msg!(
+        "Paused: {} withdrawals allowed: {}",
+        paused,
+        allow_withdrawals
+    );
+    emit(EventKind::SetPaused, admin.key, paused as u64, 0)
+}
+
+pub fn set_withdraw_delay(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    seconds: i64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let admin = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+
+    if seconds < 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut state = load_admin_config(program_id, admin, config)?;
+    state.withdraw_delay = seconds;
+    store(&state, config)?;
+
+    msg!("Withdraw delay: {}s", seconds);
+    emit(EventKind::SetWithdrawDelay, admin.key, seconds as u64, 0)
+}
+
+/// Requires `admin` to be the upgrade authority recorded in `program_data`, so
+/// nobody but the deployer can claim the config first.
+fn check_upgrade_authority(
+    program_id: &Pubkey,
+    admin: &AccountInfo,
+    program_data: &AccountInfo,
+) -> ProgramResult {
+    let expected = find_program_data_address(program_id).0;
+    if *program_data.key != expected {
+        msg!(
+            "Err: program data mismatch: {} expected: {}",
+            program_data.key,
+            expected
+        );
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if *program_data.owner != bpf_loader_upgradeable::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // ProgramData layout: u32 tag, u64 slot, then Option<Pubkey> authority.
+    let data = program_data.try_borrow_data()?;
+    let metadata = data
+        .get(..UpgradeableLoaderState::size_of_programdata_metadata())
+        .ok_or(ProgramError::InvalidAccountData)?;
+    if metadata[..4] != 3u32.to_le_bytes() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if metadata[12] != 1 || metadata[13..] != admin.key.to_bytes() {
+        msg!("Err: {} is not the upgrade authority", admin.key);
+        return Err(DepositError::Unauthorized.into());
+    }
+    Ok(())
+}
+
+/// Loads the program config and requires `admin` to be its signing admin.
+pub fn load_admin_config(
+    program_id: &Pubkey,
+    admin: &AccountInfo,
+    config: &AccountInfo,
+) -> Result<Config, ProgramError> {
+    if !admin.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let state = load_config(program_id, config)?;
+    if state.admin != *admin.key {
+        msg!("Err: {} is not the admin", admin.key);
+        return Err(DepositError::Unauthorized.into());
+    }
+    Ok(state)
+}
@@ -0,0 +1,134 @@
+use solana_deposit_interface::{
+    error::DepositError,
+    event::EventKind,
+    find_allowlist_address,
+    state::{AllowlistEntry, Config},
+    ALLOWLIST_SEED,
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    admin::load_admin_config,
+    events::emit,
+    utils::{close_account, create_pda_account, store},
+};
+
+pub fn set_allowlist_enabled(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    enabled: bool,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let admin = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+
+    let mut state = load_admin_config(program_id, admin, config)?;
+    state.allowlist_enabled = enabled;
+    store(&state, config)?;
+
+    msg!("Allowlist enabled: {}", enabled);
+    emit(EventKind::SetAllowlistEnabled, admin.key, enabled as u64, 0)
+}
+
+pub fn allowlist_add(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    address: Pubkey,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let admin = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+    let entry = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    load_admin_config(program_id, admin, config)?;
+
+    let bump = check_allowlist_marker(program_id, &address, entry)?;
+    if !entry.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    create_pda_account(
+        program_id,
+        admin,
+        entry,
+        system_program,
+        AllowlistEntry::LEN,
+        &[ALLOWLIST_SEED, address.as_ref(), &[bump]],
+    )?;
+    store(&AllowlistEntry { address }, entry)?;
+
+    msg!("Allowlisted: {}", address);
+    emit(EventKind::AllowlistAdd, &address, 0, 0)
+}
+
+pub fn allowlist_remove(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    address: Pubkey,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let admin = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+    let entry = next_account_info(accounts_iter)?;
+
+    load_admin_config(program_id, admin, config)?;
+
+    check_allowlist_marker(program_id, &address, entry)?;
+    if entry.owner != program_id || entry.data_is_empty() {
+        return Err(DepositError::NotAllowlisted.into());
+    }
+    close_account(entry, admin)?;
+
+    msg!("Removed from allowlist: {}", address);
+    emit(EventKind::AllowlistRemove, &address, 0, 0)
+}
+
+/// Ensures `entry` is the allowlist marker PDA of `address` and returns its
+/// bump seed.
+fn check_allowlist_marker(
+    program_id: &Pubkey,
+    address: &Pubkey,
+    entry: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    let (expected, bump) = find_allowlist_address(program_id, address);
+    if *entry.key != expected {
+        msg!(
+            "Err: allowlist marker mismatch: {} expected: {}",
+            entry.key,
+            expected
+        );
+        return Err(DepositError::AllowlistMismatch.into());
+    }
+    Ok(bump)
+}
+
+/// While the allowlist is enabled, takes the next account as the marker PDA
+/// of `depositor` and requires it to exist.
+pub fn check_allowlisted<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    state: &Config,
+    depositor: &AccountInfo,
+    accounts_iter: &mut impl Iterator<Item = &'a AccountInfo<'b>>,
+) -> ProgramResult {
+    // The marker slot is consumed even when disabled so later accounts keep
+    // their positions.
+    let entry = accounts_iter.next();
+    if !state.allowlist_enabled {
+        return Ok(());
+    }
+
+    let entry = entry.ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let (expected, _) = find_allowlist_address(program_id, depositor.key);
+    if *entry.key != expected || entry.owner != program_id || entry.data_is_empty() {
+        msg!("Err: {} is not allowlisted", depositor.key);
+        return Err(DepositError::NotAllowlisted.into());
+    }
+    Ok(())
+}
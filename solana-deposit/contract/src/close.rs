@@ -0,0 +1,140 @@
+use solana_deposit_interface::{
+    error::DepositError,
+    event::EventKind,
+    find_token_ledger_address,
+    state::{Delegation, TokenDepositAccount},
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    delegation::load_delegation,
+    events::emit,
+    fees::accrue_fee,
+    rewards::debit_ledger,
+    utils::{close_account, load, load_config, load_ledger, pay_from_vault, store},
+};
+
+pub fn close_ledger(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let user = next_account_info(accounts_iter)?;
+    let vault = next_account_info(accounts_iter)?;
+    let ledger = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+
+    if !user.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut state = load_config(program_id, config)?;
+    if state.withdrawals_blocked() {
+        msg!("Err: withdrawals are paused");
+        return Err(DepositError::Paused.into());
+    }
+
+    let mut deposit_account = load_ledger(program_id, user, ledger)?;
+    if deposit_account.multisig {
+        return Err(DepositError::MultisigRequired.into());
+    }
+    if deposit_account.pending_withdrawals > 0 {
+        msg!(
+            "Err: {} withdrawals still pending",
+            deposit_account.pending_withdrawals
+        );
+        return Err(DepositError::FundsLocked.into());
+    }
+
+    let balance = deposit_account.balance;
+    if balance > 0 && state.withdraw_delay > 0 {
+        return Err(DepositError::TimelockRequired.into());
+    }
+    let owned = accounts_iter.as_slice();
+    debit_ledger(&mut state, &mut deposit_account, balance)?;
+
+    // Dust below the fee goes to the fee collector instead of blocking the close.
+    let fee = state.withdraw_fee(balance).min(balance);
+    let paid = accrue_fee(&mut state, balance, fee)?;
+    store(&state, config)?;
+    if fee > 0 {
+        msg!("Withdrawal fee: {}", fee);
+    }
+    let payout = paid
+        .checked_add(deposit_account.rewards)
+        .ok_or(DepositError::Overflow)?;
+    if payout > 0 {
+        pay_from_vault(program_id, vault, user, system_program, payout)?;
+    }
+
+    let mut delegations = 0;
+    for (i, account) in owned.iter().enumerate() {
+        if owned[..i].iter().any(|seen| seen.key == account.key) {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if check_closable(program_id, user.key, account)? {
+            delegations += 1;
+        }
+    }
+    // A delegation left open would spend from the next ledger at this address.
+    if delegations != deposit_account.delegations {
+        msg!(
+            "Err: {} of {} delegations listed",
+            delegations,
+            deposit_account.delegations
+        );
+        return Err(DepositError::DelegationsOpen.into());
+    }
+    for account in owned {
+        close_account(account, user)?;
+    }
+    close_account(ledger, user)?;
+
+    msg!(
+        "Ledger closed: {} withdrawn, {} rewards, {} accounts closed",
+        balance,
+        deposit_account.rewards,
+        owned.len() + 1
+    );
+    emit(EventKind::CloseAccount, user.key, balance, 0)
+}
+
+/// Ensures `account` is an empty token ledger or a delegation of `user`, and
+/// returns whether it is a delegation.
+fn check_closable(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    account: &AccountInfo,
+) -> Result<bool, ProgramError> {
+    if account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    match account.data_len() {
+        TokenDepositAccount::LEN => {
+            let token_account = load::<TokenDepositAccount>(account)?;
+            let (expected, _) = find_token_ledger_address(program_id, user, &token_account.mint);
+            if token_account.user != *user || *account.key != expected {
+                return Err(DepositError::TokenLedgerMismatch.into());
+            }
+            if token_account.balance > 0 {
+                msg!(
+                    "Err: token ledger {} holds {}",
+                    account.key,
+                    token_account.balance
+                );
+                return Err(DepositError::FundsLocked.into());
+            }
+        }
+        Delegation::LEN => {
+            load_delegation(program_id, user, account)?;
+            return Ok(true);
+        }
+        _ => return Err(ProgramError::InvalidAccountData),
+    }
+    Ok(false)
+}
@@ -0,0 +1,175 @@
+use solana_deposit_interface::{
+    error::DepositError, event::EventKind, find_delegation_address, state::Delegation,
+    DELEGATION_SEED,
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+use crate::{
+    events::emit,
+    utils::{close_account, create_pda_account, load, load_ledger, store},
+};
+
+pub fn approve_delegate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    delegate: Pubkey,
+    allowance: u64,
+    expiry: i64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let owner = next_account_info(accounts_iter)?;
+    let ledger = next_account_info(accounts_iter)?;
+    let delegation = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    if !owner.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if delegate == *owner.key || expiry < 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut deposit_account = load_ledger(program_id, owner, ledger)?;
+    if deposit_account.multisig {
+        return Err(DepositError::MultisigRequired.into());
+    }
+
+    let (expected, bump) = find_delegation_address(program_id, owner.key, &delegate);
+    if *delegation.key != expected {
+        msg!(
+            "Err: delegation mismatch: {} expected: {}",
+            delegation.key,
+            expected
+        );
+        return Err(DepositError::DelegationMismatch.into());
+    }
+    if delegation.data_is_empty() {
+        create_pda_account(
+            program_id,
+            owner,
+            delegation,
+            system_program,
+            Delegation::LEN,
+            &[
+                DELEGATION_SEED,
+                owner.key.as_ref(),
+                delegate.as_ref(),
+                &[bump],
+            ],
+        )?;
+        deposit_account.delegations = deposit_account
+            .delegations
+            .checked_add(1)
+            .ok_or(DepositError::Overflow)?;
+        store(&deposit_account, ledger)?;
+    } else if delegation.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let grant = Delegation {
+        owner: *owner.key,
+        delegate,
+        allowance,
+        expiry,
+    };
+    store(&grant, delegation)?;
+
+    msg!(
+        "Delegate {} may withdraw {} until {}",
+        delegate,
+        allowance,
+        expiry
+    );
+    emit(
+        EventKind::ApproveDelegate,
+        owner.key,
+        allowance,
+        deposit_account.balance,
+    )
+}
+
+pub fn revoke_delegate(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let owner = next_account_info(accounts_iter)?;
+    let ledger = next_account_info(accounts_iter)?;
+    let delegation = next_account_info(accounts_iter)?;
+
+    if !owner.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let grant = load_delegation(program_id, owner.key, delegation)?;
+    let mut deposit_account = load_ledger(program_id, owner, ledger)?;
+    deposit_account.delegations = deposit_account.delegations.saturating_sub(1);
+    store(&deposit_account, ledger)?;
+    close_account(delegation, owner)?;
+
+    msg!("Delegate {} revoked", grant.delegate);
+    emit(EventKind::RevokeDelegate, owner.key, 0, 0)
+}
+
+/// Authorizes a withdrawal from `owner`'s ledger by the delegate that follows
+/// in `accounts_iter`, spending `amount` of its allowance.
+pub fn spend_allowance<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    owner: &AccountInfo<'b>,
+    accounts_iter: &mut impl Iterator<Item = &'a AccountInfo<'b>>,
+    amount: u64,
+) -> ProgramResult {
+    // Without a delegate the withdrawal simply lacks the owner's signature.
+    let delegate =
+        next_account_info(accounts_iter).map_err(|_| ProgramError::MissingRequiredSignature)?;
+    let delegation = next_account_info(accounts_iter)?;
+
+    if !delegate.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut grant = load_delegation(program_id, owner.key, delegation)?;
+    if grant.delegate != *delegate.key {
+        return Err(DepositError::DelegationMismatch.into());
+    }
+    if grant.is_expired(Clock::get()?.unix_timestamp) {
+        return Err(DepositError::DelegationExpired.into());
+    }
+    if amount > grant.allowance {
+        msg!("Err: allowance: {} need: {}", grant.allowance, amount);
+        return Err(DepositError::AllowanceExceeded.into());
+    }
+    grant.allowance -= amount;
+    store(&grant, delegation)?;
+
+    msg!(
+        "Delegate {} withdrew {}, allowance left {}",
+        delegate.key,
+        amount,
+        grant.allowance
+    );
+    Ok(())
+}
+
+/// Loads a delegation after checking it is the PDA of `owner` and its delegate.
+pub fn load_delegation(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    delegation: &AccountInfo,
+) -> Result<Delegation, ProgramError> {
+    if delegation.owner != program_id || delegation.data_is_empty() {
+        return Err(DepositError::DelegationMismatch.into());
+    }
+
+    let grant = load::<Delegation>(delegation)?;
+    let (expected, _) = find_delegation_address(program_id, owner, &grant.delegate);
+    if grant.owner != *owner || *delegation.key != expected {
+        return Err(DepositError::DelegationMismatch.into());
+    }
+    Ok(grant)
+}
@@ -0,0 +1,244 @@
+use solana_deposit_interface::{
+    error::DepositError, event::EventKind, find_escrow_address, state::Escrow, ESCROW_SEED,
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+use crate::{
+    allowlist::check_allowlisted,
+    events::emit,
+    fees::{accrue_fee, pay_withdrawal},
+    limits::check_deposit_limits,
+    utils::{
+        check_vault, close_account, create_pda_account, load, load_config, store, transfer_to_vault,
+    },
+};
+
+pub fn escrow_create(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    id: u64,
+    amount: u64,
+    counterparty: Pubkey,
+    timeout: i64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let depositor = next_account_info(accounts_iter)?;
+    let vault = next_account_info(accounts_iter)?;
+    let escrow = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+
+    if !depositor.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if timeout < 0 || counterparty == *depositor.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut state = load_config(program_id, config)?;
+    if state.paused {
+        msg!("Err: deposits are paused");
+        return Err(DepositError::Paused.into());
+    }
+    check_allowlisted(program_id, &state, depositor, accounts_iter)?;
+
+    check_vault(program_id, vault)?;
+    check_deposit_limits(&state, amount)?;
+    let (expected, bump) = find_escrow_address(program_id, depositor.key, id);
+    if *escrow.key != expected {
+        msg!(
+            "Err: escrow mismatch: {} expected: {}",
+            escrow.key,
+            expected
+        );
+        return Err(DepositError::EscrowMismatch.into());
+    }
+    if !escrow.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let fee = state.deposit_fee(amount);
+    let locked = accrue_fee(&mut state, amount, fee)?;
+    state.total_locked = state
+        .total_locked
+        .checked_add(locked)
+        .ok_or(DepositError::Overflow)?;
+
+    create_pda_account(
+        program_id,
+        depositor,
+        escrow,
+        system_program,
+        Escrow::LEN,
+        &[
+            ESCROW_SEED,
+            depositor.key.as_ref(),
+            &id.to_le_bytes(),
+            &[bump],
+        ],
+    )?;
+    transfer_to_vault(depositor, vault, system_program, amount)?;
+
+    let refund_after = Clock::get()?
+        .unix_timestamp
+        .checked_add(timeout)
+        .ok_or(DepositError::Overflow)?;
+    let deal = Escrow {
+        depositor: *depositor.key,
+        counterparty,
+        id,
+        amount: locked,
+        released: false,
+        refund_after,
+    };
+    store(&deal, escrow)?;
+    store(&state, config)?;
+    if fee > 0 {
+        msg!("Deposit fee: {}", fee);
+    }
+
+    msg!(
+        "Escrow {} of {} for {} refundable after {}",
+        id,
+        locked,
+        counterparty,
+        refund_after
+    );
+    emit(EventKind::EscrowCreate, depositor.key, amount, locked)
+}
+
+pub fn escrow_release(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let depositor = next_account_info(accounts_iter)?;
+    let escrow = next_account_info(accounts_iter)?;
+
+    if !depositor.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut deal = load_escrow(program_id, depositor.key, escrow)?;
+    deal.released = true;
+    store(&deal, escrow)?;
+
+    msg!("Escrow {} released to {}", deal.id, deal.counterparty);
+    emit(EventKind::EscrowRelease, depositor.key, deal.amount, 0)
+}
+
+pub fn escrow_claim(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let counterparty = next_account_info(accounts_iter)?;
+    let depositor = next_account_info(accounts_iter)?;
+    let vault = next_account_info(accounts_iter)?;
+    let escrow = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+
+    if !counterparty.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut state = load_config(program_id, config)?;
+    if state.withdrawals_blocked() {
+        msg!("Err: withdrawals are paused");
+        return Err(DepositError::Paused.into());
+    }
+
+    let deal = load_escrow(program_id, depositor.key, escrow)?;
+    if deal.counterparty != *counterparty.key {
+        return Err(DepositError::EscrowMismatch.into());
+    }
+    if !deal.released {
+        return Err(DepositError::EscrowNotReleased.into());
+    }
+
+    state.total_locked = state.total_locked.saturating_sub(deal.amount);
+    store(&state, config)?;
+    pay_withdrawal(
+        program_id,
+        &mut state,
+        config,
+        vault,
+        counterparty,
+        system_program,
+        deal.amount,
+    )?;
+    close_account(escrow, depositor)?;
+
+    msg!("Escrow {} claimed", deal.id);
+    emit(EventKind::EscrowClaim, counterparty.key, deal.amount, 0)
+}
+
+pub fn escrow_refund(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let depositor = next_account_info(accounts_iter)?;
+    let vault = next_account_info(accounts_iter)?;
+    let escrow = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+
+    if !depositor.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut state = load_config(program_id, config)?;
+    if state.withdrawals_blocked() {
+        msg!("Err: withdrawals are paused");
+        return Err(DepositError::Paused.into());
+    }
+
+    let deal = load_escrow(program_id, depositor.key, escrow)?;
+    if deal.released {
+        return Err(DepositError::EscrowReleased.into());
+    }
+    let now = Clock::get()?.unix_timestamp;
+    if now < deal.refund_after {
+        msg!(
+            "Err: escrow {} refundable after {}",
+            deal.id,
+            deal.refund_after
+        );
+        return Err(DepositError::EscrowLocked.into());
+    }
+
+    state.total_locked = state.total_locked.saturating_sub(deal.amount);
+    store(&state, config)?;
+    pay_withdrawal(
+        program_id,
+        &mut state,
+        config,
+        vault,
+        depositor,
+        system_program,
+        deal.amount,
+    )?;
+    close_account(escrow, depositor)?;
+
+    msg!("Escrow {} refunded", deal.id);
+    emit(EventKind::EscrowRefund, depositor.key, deal.amount, 0)
+}
+
+/// Loads an escrow after checking it is the PDA of `depositor` for its id.
+fn load_escrow(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    escrow: &AccountInfo,
+) -> Result<Escrow, ProgramError> {
+    if escrow.owner != program_id || escrow.data_is_empty() {
+        return Err(DepositError::EscrowMismatch.into());
+    }
+
+    let deal = load::<Escrow>(escrow)?;
+    let (expected, _) = find_escrow_address(program_id, depositor, deal.id);
+    if deal.depositor != *depositor || *escrow.key != expected {
+        return Err(DepositError::EscrowMismatch.into());
+    }
+    Ok(deal)
+}
@@ -0,0 +1,17 @@
+use solana_deposit_interface::event::{Event, EventKind};
+use solana_program::{
+    clock::Clock, entrypoint::ProgramResult, log::sol_log_data, pubkey::Pubkey, sysvar::Sysvar,
+};
+
+/// Logs an [`Event`] for `kind` stamped with the current slot.
+pub fn emit(kind: EventKind, user: &Pubkey, amount: u64, balance: u64) -> ProgramResult {
+    let event = Event {
+        kind,
+        user: *user,
+        amount,
+        balance,
+        slot: Clock::get()?.slot,
+    };
+    sol_log_data(&[&event.encode()]);
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use solana_deposit_interface::{
+    error::DepositError,
+    event::EventKind,
+    state::{Config, MAX_FEE_BPS},
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    admin::load_admin_config,
+    events::emit,
+    utils::{load_config, pay_from_vault, store},
+};
+
+pub fn set_fees(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    deposit_fee_bps: u16,
+    withdraw_fee_bps: u16,
+    min_fee: u64,
+    fee_collector: Pubkey,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let admin = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+
+    if deposit_fee_bps > MAX_FEE_BPS || withdraw_fee_bps > MAX_FEE_BPS {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut state = load_admin_config(program_id, admin, config)?;
+    state.deposit_fee_bps = deposit_fee_bps;
+    state.withdraw_fee_bps = withdraw_fee_bps;
+    state.min_fee = min_fee;
+    state.fee_collector = fee_collector;
+    store(&state, config)?;
+
+    msg!(
+        "Fees: deposit {} bps withdraw {} bps min {} collector {}",
+        deposit_fee_bps,
+        withdraw_fee_bps,
+        min_fee,
+        fee_collector
+    );
+    emit(EventKind::SetFees, admin.key, 0, 0)
+}
+
+pub fn collect_fees(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let fee_collector = next_account_info(accounts_iter)?;
+    let vault = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    if !fee_collector.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut state = load_config(program_id, config)?;
+    if state.fee_collector == Pubkey::default() || state.fee_collector != *fee_collector.key {
+        msg!("Err: {} is not the fee collector", fee_collector.key);
+        return Err(DepositError::Unauthorized.into());
+    }
+
+    let amount = state.accrued_fees;
+    state.accrued_fees = 0;
+    store(&state, config)?;
+
+    pay_from_vault(program_id, vault, fee_collector, system_program, amount)?;
+
+    msg!("Fees collected: {}", amount);
+    emit(EventKind::CollectFees, fee_collector.key, amount, 0)
+}
+
+/// Pays `amount` out of the vault minus the withdrawal fee, which is accrued
+/// in `config`.
+pub fn pay_withdrawal<'a>(
+    program_id: &Pubkey,
+    state: &mut Config,
+    config: &AccountInfo<'a>,
+    vault: &AccountInfo<'a>,
+    recipient: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    amount: u64,
+) -> ProgramResult {
+    let fee = state.withdraw_fee(amount);
+    let paid = accrue_fee(state, amount, fee)?;
+    if fee > 0 {
+        store(state, config)?;
+        msg!("Withdrawal fee: {}", fee);
+    }
+
+    pay_from_vault(program_id, vault, recipient, system_program, paid)
+}
+
+/// Adds `fee` to the fees accrued in `state` and returns what is left of `amount`.
+pub fn accrue_fee(state: &mut Config, amount: u64, fee: u64) -> Result<u64, ProgramError> {
+    if fee > amount {
+        msg!("Err: amount {} is below the fee {}", amount, fee);
+        return Err(DepositError::AmountBelowFee.into());
+    }
+    state.accrued_fees = state
+        .accrued_fees
+        .checked_add(fee)
+        .ok_or(DepositError::Overflow)?;
+    Ok(amount - fee)
+}
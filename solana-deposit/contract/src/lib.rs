@@ -1,111 +1,352 @@
+mod admin;
+mod allowlist;
+mod close;
+mod delegation;
+mod escrow;
+mod events;
+mod fees;
+mod limits;
+mod multisig;
+mod rewards;
+mod shares;
+mod timelock;
+mod token;
+mod utils;
+mod vesting;
+
+use borsh::BorshDeserialize;
+use solana_deposit_interface::{
+    error::DepositError, event::EventKind, instruction::ProgramInstruction, state::DepositAccount,
+    LEDGER_SEED,
+};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint,
     entrypoint::ProgramResult,
     msg,
-    program::invoke,
     program_error::ProgramError,
     pubkey::Pubkey,
-    system_instruction,
+};
+
+use allowlist::check_allowlisted;
+use delegation::spend_allowance;
+use events::emit;
+use fees::{accrue_fee, pay_withdrawal};
+use limits::{check_deposit_limits, check_user_cap};
+use rewards::{credit_ledger, debit_ledger};
+use shares::mint_shares;
+use utils::{
+    check_ledger, check_vault, create_pda_account, load, load_config, load_ledger, store,
+    transfer_to_vault,
 };
 
 entrypoint!(process_instruction);
 
 pub fn process_instruction(
-    _program_id: &Pubkey,
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
     data: &[u8],
 ) -> ProgramResult {
-    let actual_data = if data.len() >= 8 { &data[8..] } else { data };
+    let instruction = ProgramInstruction::try_from_slice(data).map_err(|e| {
+        msg!("Err: invalide data: {}", e);
+        ProgramError::InvalidInstructionData
+    })?;
 
-    if actual_data.is_empty() {
-        msg!("Err: empty data");
-        return Err(ProgramError::InvalidInstructionData);
-    }
-
-    let instruction_type = actual_data[0];
-
-    if actual_data.len() < 9 {
-        msg!("Err: invalide data: {}", actual_data.len());
-        return Err(ProgramError::InvalidInstructionData);
-    }
-
-    let amount = u64::from_le_bytes([
-        actual_data[1],
-        actual_data[2],
-        actual_data[3],
-        actual_data[4],
-        actual_data[5],
-        actual_data[6],
-        actual_data[7],
-        actual_data[8],
-    ]);
-    match instruction_type {
-        0 => {
+    match instruction {
+        ProgramInstruction::Deposit { amount } => {
             msg!("running dep");
-            deposit(accounts, amount)
+            deposit(program_id, accounts, amount)
         }
-        1 => {
+        ProgramInstruction::Withdraw { amount } => {
             msg!("running withdrawal ");
-            withdraw(accounts, amount)
+            withdraw(program_id, accounts, amount)
+        }
+        ProgramInstruction::Initialize => {
+            msg!("running initialize");
+            admin::initialize(program_id, accounts)
+        }
+        ProgramInstruction::SetAuthority { new_authority } => {
+            msg!("running set authority");
+            admin::set_authority(program_id, accounts, new_authority)
+        }
+        ProgramInstruction::AcceptAuthority => {
+            msg!("running accept authority");
+            admin::accept_authority(program_id, accounts)
+        }
+        ProgramInstruction::SetPaused {
+            paused,
+            allow_withdrawals,
+        } => {
+            msg!("running set paused");
+            admin::set_paused(program_id, accounts, paused, allow_withdrawals)
+        }
+        ProgramInstruction::SetWithdrawDelay { seconds } => {
+            msg!("running set withdraw delay");
+            admin::set_withdraw_delay(program_id, accounts, seconds)
+        }
+        ProgramInstruction::RequestWithdraw { amount } => {
+            msg!("running request withdraw");
+            timelock::request_withdraw(program_id, accounts, amount)
+        }
+        ProgramInstruction::ClaimWithdraw => {
+            msg!("running claim withdraw");
+            timelock::claim_withdraw(program_id, accounts)
+        }
+        ProgramInstruction::CancelWithdraw => {
+            msg!("running cancel withdraw");
+            timelock::cancel_withdraw(program_id, accounts)
+        }
+        ProgramInstruction::DepositToken { amount } => {
+            msg!("running token dep");
+            token::deposit_token(program_id, accounts, amount)
+        }
+        ProgramInstruction::WithdrawToken { amount } => {
+            msg!("running token withdrawal");
+            token::withdraw_token(program_id, accounts, amount)
+        }
+        ProgramInstruction::SetFees {
+            deposit_fee_bps,
+            withdraw_fee_bps,
+            min_fee,
+            fee_collector,
+        } => {
+            msg!("running set fees");
+            fees::set_fees(
+                program_id,
+                accounts,
+                deposit_fee_bps,
+                withdraw_fee_bps,
+                min_fee,
+                fee_collector,
+            )
+        }
+        ProgramInstruction::CollectFees => {
+            msg!("running collect fees");
+            fees::collect_fees(program_id, accounts)
+        }
+        ProgramInstruction::SetMultisig { signers, threshold } => {
+            msg!("running set multisig");
+            multisig::set_multisig(program_id, accounts, signers, threshold)
+        }
+        ProgramInstruction::ProposeWithdraw {
+            amount,
+            recipient,
+            expires_in,
+        } => {
+            msg!("running propose withdraw");
+            multisig::propose_withdraw(program_id, accounts, amount, recipient, expires_in)
+        }
+        ProgramInstruction::ApproveWithdraw => {
+            msg!("running approve withdraw");
+            multisig::approve_withdraw(program_id, accounts)
+        }
+        ProgramInstruction::ExecuteWithdraw => {
+            msg!("running execute withdraw");
+            multisig::execute_withdraw(program_id, accounts)
+        }
+        ProgramInstruction::CancelProposal => {
+            msg!("running cancel proposal");
+            multisig::cancel_proposal(program_id, accounts)
+        }
+        ProgramInstruction::DepositVested {
+            amount,
+            beneficiary,
+            start,
+            cliff,
+            end,
+        } => {
+            msg!("running vested dep");
+            vesting::deposit_vested(program_id, accounts, amount, beneficiary, start, cliff, end)
+        }
+        ProgramInstruction::WithdrawVested { amount } => {
+            msg!("running vested withdrawal");
+            vesting::withdraw_vested(program_id, accounts, amount)
+        }
+        ProgramInstruction::EscrowCreate {
+            id,
+            amount,
+            counterparty,
+            timeout,
+        } => {
+            msg!("running escrow create");
+            escrow::escrow_create(program_id, accounts, id, amount, counterparty, timeout)
         }
-        _ => {
-            msg!("Err: invalide: {}", instruction_type);
-            Err(ProgramError::InvalidInstructionData)
+        ProgramInstruction::EscrowRelease => {
+            msg!("running escrow release");
+            escrow::escrow_release(program_id, accounts)
+        }
+        ProgramInstruction::EscrowClaim => {
+            msg!("running escrow claim");
+            escrow::escrow_claim(program_id, accounts)
+        }
+        ProgramInstruction::EscrowRefund => {
+            msg!("running escrow refund");
+            escrow::escrow_refund(program_id, accounts)
+        }
+        ProgramInstruction::SetLimits {
+            max_deposit,
+            max_user_balance,
+            max_tvl,
+        } => {
+            msg!("running set limits");
+            limits::set_limits(program_id, accounts, max_deposit, max_user_balance, max_tvl)
+        }
+        ProgramInstruction::SetAllowlistEnabled { enabled } => {
+            msg!("running set allowlist enabled");
+            allowlist::set_allowlist_enabled(program_id, accounts, enabled)
+        }
+        ProgramInstruction::AllowlistAdd { address } => {
+            msg!("running allowlist add");
+            allowlist::allowlist_add(program_id, accounts, address)
+        }
+        ProgramInstruction::AllowlistRemove { address } => {
+            msg!("running allowlist remove");
+            allowlist::allowlist_remove(program_id, accounts, address)
+        }
+        ProgramInstruction::ApproveDelegate {
+            delegate,
+            allowance,
+            expiry,
+        } => {
+            msg!("running approve delegate");
+            delegation::approve_delegate(program_id, accounts, delegate, allowance, expiry)
+        }
+        ProgramInstruction::RevokeDelegate => {
+            msg!("running revoke delegate");
+            delegation::revoke_delegate(program_id, accounts)
+        }
+        ProgramInstruction::CloseAccount => {
+            msg!("running close account");
+            close::close_ledger(program_id, accounts)
+        }
+        ProgramInstruction::CreateShareMint => {
+            msg!("running create share mint");
+            shares::create_share_mint(program_id, accounts)
+        }
+        ProgramInstruction::DistributeRewards { amount } => {
+            msg!("running distribute rewards");
+            rewards::distribute_rewards(program_id, accounts, amount)
+        }
+        ProgramInstruction::ClaimRewards => {
+            msg!("running claim rewards");
+            rewards::claim_rewards(program_id, accounts)
+        }
+        ProgramInstruction::RedeemShares { shares } => {
+            msg!("running redeem shares");
+            shares::redeem_shares(program_id, accounts, shares)
         }
     }
 }
 
-fn deposit(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+fn deposit(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
     let user = next_account_info(accounts_iter)?;
-    let deposit_account = next_account_info(accounts_iter)?;
+    let vault = next_account_info(accounts_iter)?;
+    let ledger = next_account_info(accounts_iter)?;
     let system_program = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
 
     if !user.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    invoke(
-        &system_instruction::transfer(user.key, deposit_account.key, amount),
-        &[
-            user.clone(),
-            deposit_account.clone(),
-            system_program.clone(),
-        ],
-    )?;
+    let mut state = load_config(program_id, config)?;
+    if state.paused {
+        msg!("Err: deposits are paused");
+        return Err(DepositError::Paused.into());
+    }
+    check_allowlisted(program_id, &state, user, accounts_iter)?;
 
-    Ok(())
+    check_vault(program_id, vault)?;
+    check_deposit_limits(&state, amount)?;
+    let ledger_bump = check_ledger(program_id, user, ledger)?;
+
+    if ledger.data_is_empty() {
+        create_pda_account(
+            program_id,
+            user,
+            ledger,
+            system_program,
+            DepositAccount::LEN,
+            &[LEDGER_SEED, user.key.as_ref(), &[ledger_bump]],
+        )?;
+        msg!("Ledger created: {}", ledger.key);
+    } else if ledger.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let fee = state.deposit_fee(amount);
+    let credited = accrue_fee(&mut state, amount, fee)?;
+
+    let mut deposit_account = load::<DepositAccount>(ledger)?;
+    // Receipts, not the ledger, carry the claim on share deposits; proposals
+    // cannot redeem receipts, so multisig ledgers are always credited.
+    if state.shares_enabled && !deposit_account.multisig {
+        mint_shares(program_id, &mut state, user, accounts_iter, credited)?;
+    } else {
+        credit_ledger(&mut state, &mut deposit_account, credited)?;
+        check_user_cap(&state, deposit_account.balance)?;
+    }
+
+    transfer_to_vault(user, vault, system_program, amount)?;
+
+    store(&deposit_account, ledger)?;
+    store(&state, config)?;
+    if fee > 0 {
+        msg!("Deposit fee: {}", fee);
+    }
+
+    emit(
+        EventKind::Deposit,
+        user.key,
+        amount,
+        deposit_account.balance,
+    )
 }
 
-fn withdraw(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+fn withdraw(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
     let user = next_account_info(accounts_iter)?;
-    let deposit_account = next_account_info(accounts_iter)?;
+    let vault = next_account_info(accounts_iter)?;
+    let ledger = next_account_info(accounts_iter)?;
     let system_program = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
 
-    if !user.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
+    let mut state = load_config(program_id, config)?;
+    if state.withdrawals_blocked() {
+        msg!("Err: withdrawals are paused");
+        return Err(DepositError::Paused.into());
+    }
+    if state.withdraw_delay > 0 {
+        return Err(DepositError::TimelockRequired.into());
     }
 
-    if deposit_account.lamports() < amount {
-        msg!(
-            "Insufficient funds: {} need: {}",
-            deposit_account.lamports(),
-            amount
-        );
-        return Err(ProgramError::InsufficientFunds);
+    let mut deposit_account = load_ledger(program_id, user, ledger)?;
+    if deposit_account.multisig {
+        return Err(DepositError::MultisigRequired.into());
+    }
+    if !user.is_signer {
+        spend_allowance(program_id, user, accounts_iter, amount)?;
     }
+    debit_ledger(&mut state, &mut deposit_account, amount)?;
+    store(&deposit_account, ledger)?;
+    store(&state, config)?;
 
-    invoke(
-        &system_instruction::transfer(deposit_account.key, user.key, amount),
-        &[
-            deposit_account.clone(),
-            user.clone(),
-            system_program.clone(),
-        ],
+    pay_withdrawal(
+        program_id,
+        &mut state,
+        config,
+        vault,
+        user,
+        system_program,
+        amount,
     )?;
 
     msg!("Withdrawal completed");
-    Ok(())
+    emit(
+        EventKind::Withdraw,
+        user.key,
+        amount,
+        deposit_account.balance,
+    )
 }
@@ -0,0 +1,71 @@
+use solana_deposit_interface::{error::DepositError, event::EventKind, state::Config};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    pubkey::Pubkey,
+};
+
+use crate::{admin::load_admin_config, events::emit, utils::store};
+
+pub fn set_limits(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    max_deposit: u64,
+    max_user_balance: u64,
+    max_tvl: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let admin = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+
+    let mut state = load_admin_config(program_id, admin, config)?;
+    state.max_deposit = max_deposit;
+    state.max_user_balance = max_user_balance;
+    state.max_tvl = max_tvl;
+    store(&state, config)?;
+
+    msg!(
+        "Limits: deposit {} user balance {} tvl {}",
+        max_deposit,
+        max_user_balance,
+        max_tvl
+    );
+    emit(EventKind::SetLimits, admin.key, 0, 0)
+}
+
+/// Rejects a deposit of `amount` lamports that breaks the per-transaction or
+/// total value locked limit.
+pub fn check_deposit_limits(state: &Config, amount: u64) -> ProgramResult {
+    if state.max_deposit > 0 && amount > state.max_deposit {
+        msg!("Err: deposit {} over limit {}", amount, state.max_deposit);
+        return Err(DepositError::DepositTooLarge.into());
+    }
+
+    // Fees and rewards sit in the vault too, but are not deposits, so only
+    // what is left after the deposit fee counts.
+    let net = amount.saturating_sub(state.deposit_fee(amount));
+    let tvl = state.tvl().saturating_add(net);
+    if state.max_tvl > 0 && tvl > state.max_tvl {
+        msg!(
+            "Err: deposits would total {} over cap {}",
+            tvl,
+            state.max_tvl
+        );
+        return Err(DepositError::TvlCapExceeded.into());
+    }
+    Ok(())
+}
+
+/// Rejects a ledger `balance` above the per-user cap.
+pub fn check_user_cap(state: &Config, balance: u64) -> ProgramResult {
+    if state.max_user_balance > 0 && balance > state.max_user_balance {
+        msg!(
+            "Err: balance {} over cap {}",
+            balance,
+            state.max_user_balance
+        );
+        return Err(DepositError::UserCapExceeded.into());
+    }
+    Ok(())
+}
@@ -0,0 +1,368 @@
+use solana_deposit_interface::{
+    error::DepositError,
+    event::EventKind,
+    find_ledger_address, find_multisig_address, find_proposal_address,
+    state::{DepositAccount, Multisig, WithdrawalProposal, MAX_MULTISIG_SIGNERS},
+    MULTISIG_SEED, PROPOSAL_SEED,
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+use crate::{
+    events::emit,
+    fees::pay_withdrawal,
+    rewards::debit_ledger,
+    utils::{close_account, create_pda_account, load, load_config, load_ledger, store},
+};
+
+pub fn set_multisig(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    signers: Vec<Pubkey>,
+    threshold: u8,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let owner = next_account_info(accounts_iter)?;
+    let ledger = next_account_info(accounts_iter)?;
+    let multisig = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+
+    if !owner.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut unique = signers.clone();
+    unique.sort();
+    unique.dedup();
+    if signers.is_empty()
+        || signers.len() > MAX_MULTISIG_SIGNERS
+        || unique.len() != signers.len()
+        || threshold == 0
+        // The signer set is permanent; a spare signer covers one lost key.
+        || threshold as usize >= signers.len()
+    {
+        msg!(
+            "Err: invalid multisig: {} signers threshold {}",
+            signers.len(),
+            threshold
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut deposit_account = load_ledger(program_id, owner, ledger)?;
+    if deposit_account.multisig {
+        msg!("Err: multisig already configured");
+        return Err(DepositError::MultisigRequired.into());
+    }
+
+    let (expected, bump) = find_multisig_address(program_id, owner.key);
+    if *multisig.key != expected {
+        return Err(DepositError::MultisigMismatch.into());
+    }
+    create_pda_account(
+        program_id,
+        owner,
+        multisig,
+        system_program,
+        Multisig::LEN,
+        &[MULTISIG_SEED, owner.key.as_ref(), &[bump]],
+    )?;
+
+    let signer_set = Multisig {
+        owner: *owner.key,
+        threshold,
+        next_proposal_id: 0,
+        signers,
+    };
+    store(&signer_set, multisig)?;
+
+    // The ledger leaves the reward base; what it earned so far joins the
+    // balance, where proposals can pay it out.
+    let mut state = load_config(program_id, config)?;
+    deposit_account.settle_rewards(state.reward_index);
+    let rewards = std::mem::take(&mut deposit_account.rewards);
+    state.total_deposits = state.total_deposits.saturating_sub(deposit_account.balance);
+    deposit_account.credit(rewards)?;
+    deposit_account.multisig = true;
+    store(&deposit_account, ledger)?;
+    store(&state, config)?;
+
+    msg!(
+        "Multisig {}-of-{} for {}",
+        threshold,
+        signer_set.signers.len(),
+        owner.key
+    );
+    emit(
+        EventKind::SetMultisig,
+        owner.key,
+        threshold as u64,
+        deposit_account.balance,
+    )
+}
+
+pub fn propose_withdraw(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+    recipient: Pubkey,
+    expires_in: i64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let proposer = next_account_info(accounts_iter)?;
+    let multisig = next_account_info(accounts_iter)?;
+    let proposal = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+
+    if expires_in <= 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let state = load_config(program_id, config)?;
+    if state.withdrawals_blocked() {
+        msg!("Err: withdrawals are paused");
+        return Err(DepositError::Paused.into());
+    }
+
+    let mut signer_set = load_multisig(program_id, multisig)?;
+    let index = signer_index(&signer_set, proposer)?;
+
+    let id = signer_set.next_proposal_id;
+    let (expected, bump) = find_proposal_address(program_id, &signer_set.owner, id);
+    if *proposal.key != expected {
+        msg!(
+            "Err: proposal mismatch: {} expected: {}",
+            proposal.key,
+            expected
+        );
+        return Err(DepositError::ProposalMismatch.into());
+    }
+
+    signer_set.next_proposal_id = id.checked_add(1).ok_or(DepositError::Overflow)?;
+    store(&signer_set, multisig)?;
+
+    create_pda_account(
+        program_id,
+        proposer,
+        proposal,
+        system_program,
+        WithdrawalProposal::LEN,
+        &[
+            PROPOSAL_SEED,
+            signer_set.owner.as_ref(),
+            &id.to_le_bytes(),
+            &[bump],
+        ],
+    )?;
+
+    let now = Clock::get()?.unix_timestamp;
+    let pending = WithdrawalProposal {
+        owner: signer_set.owner,
+        id,
+        proposer: *proposer.key,
+        recipient,
+        amount,
+        approvals: 1 << index,
+        unlock_at: now
+            .checked_add(state.withdraw_delay)
+            .ok_or(DepositError::Overflow)?,
+        expires_at: now.checked_add(expires_in).ok_or(DepositError::Overflow)?,
+    };
+    store(&pending, proposal)?;
+
+    msg!(
+        "Proposal {} of {} to {} expires at {}",
+        id,
+        amount,
+        recipient,
+        pending.expires_at
+    );
+    emit(EventKind::ProposeWithdraw, &pending.owner, amount, 0)
+}
+
+pub fn approve_withdraw(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let signer = next_account_info(accounts_iter)?;
+    let multisig = next_account_info(accounts_iter)?;
+    let proposal = next_account_info(accounts_iter)?;
+
+    let signer_set = load_multisig(program_id, multisig)?;
+    let index = signer_index(&signer_set, signer)?;
+    let mut pending = load_proposal(program_id, &signer_set, proposal)?;
+
+    if Clock::get()?.unix_timestamp > pending.expires_at {
+        return Err(DepositError::ProposalExpired.into());
+    }
+    if pending.is_approved_by(index) {
+        return Err(DepositError::AlreadyApproved.into());
+    }
+
+    pending.approvals |= 1 << index;
+    store(&pending, proposal)?;
+
+    msg!(
+        "Proposal {} approved by {} ({}/{})",
+        pending.id,
+        signer.key,
+        pending.approval_count(),
+        signer_set.threshold
+    );
+    emit(
+        EventKind::ApproveWithdraw,
+        &pending.owner,
+        pending.amount,
+        0,
+    )
+}
+
+pub fn execute_withdraw(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let multisig = next_account_info(accounts_iter)?;
+    let proposal = next_account_info(accounts_iter)?;
+    let ledger = next_account_info(accounts_iter)?;
+    let vault = next_account_info(accounts_iter)?;
+    let recipient = next_account_info(accounts_iter)?;
+    let proposer = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+
+    let mut state = load_config(program_id, config)?;
+    if state.withdrawals_blocked() {
+        msg!("Err: withdrawals are paused");
+        return Err(DepositError::Paused.into());
+    }
+
+    let signer_set = load_multisig(program_id, multisig)?;
+    let pending = load_proposal(program_id, &signer_set, proposal)?;
+    if *recipient.key != pending.recipient || *proposer.key != pending.proposer {
+        return Err(DepositError::ProposalMismatch.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    if now > pending.expires_at {
+        return Err(DepositError::ProposalExpired.into());
+    }
+    if now < pending.unlock_at {
+        msg!(
+            "Err: proposal {} unlocks at {}",
+            pending.id,
+            pending.unlock_at
+        );
+        return Err(DepositError::WithdrawalLocked.into());
+    }
+    if pending.approval_count() < signer_set.threshold as u32 {
+        msg!(
+            "Err: {} of {} approvals",
+            pending.approval_count(),
+            signer_set.threshold
+        );
+        return Err(DepositError::ThresholdNotMet.into());
+    }
+
+    if *ledger.key != find_ledger_address(program_id, &signer_set.owner).0 {
+        return Err(DepositError::LedgerMismatch.into());
+    }
+    if ledger.owner != program_id || ledger.data_is_empty() {
+        return Err(DepositError::LedgerNotFound.into());
+    }
+    let mut deposit_account = load::<DepositAccount>(ledger)?;
+    debit_ledger(&mut state, &mut deposit_account, pending.amount)?;
+    store(&deposit_account, ledger)?;
+    store(&state, config)?;
+
+    pay_withdrawal(
+        program_id,
+        &mut state,
+        config,
+        vault,
+        recipient,
+        system_program,
+        pending.amount,
+    )?;
+    close_account(proposal, proposer)?;
+
+    msg!("Proposal {} executed", pending.id);
+    emit(
+        EventKind::ExecuteWithdraw,
+        &signer_set.owner,
+        pending.amount,
+        deposit_account.balance,
+    )
+}
+
+pub fn cancel_proposal(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let signer = next_account_info(accounts_iter)?;
+    let multisig = next_account_info(accounts_iter)?;
+    let proposal = next_account_info(accounts_iter)?;
+    let proposer = next_account_info(accounts_iter)?;
+
+    if !signer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let signer_set = load_multisig(program_id, multisig)?;
+    let pending = load_proposal(program_id, &signer_set, proposal)?;
+    if *proposer.key != pending.proposer {
+        return Err(DepositError::ProposalMismatch.into());
+    }
+
+    let expired = Clock::get()?.unix_timestamp > pending.expires_at;
+    if !expired && *signer.key != pending.proposer && *signer.key != signer_set.owner {
+        return Err(DepositError::Unauthorized.into());
+    }
+
+    close_account(proposal, proposer)?;
+
+    msg!("Proposal {} cancelled", pending.id);
+    emit(EventKind::CancelProposal, &pending.owner, pending.amount, 0)
+}
+
+fn load_multisig(program_id: &Pubkey, multisig: &AccountInfo) -> Result<Multisig, ProgramError> {
+    if multisig.owner != program_id || multisig.data_is_empty() {
+        return Err(DepositError::MultisigMismatch.into());
+    }
+
+    let signer_set = load::<Multisig>(multisig)?;
+    if *multisig.key != find_multisig_address(program_id, &signer_set.owner).0 {
+        return Err(DepositError::MultisigMismatch.into());
+    }
+    Ok(signer_set)
+}
+
+/// Requires `signer` to sign and be part of `signer_set`, returning its index.
+fn signer_index(signer_set: &Multisig, signer: &AccountInfo) -> Result<usize, ProgramError> {
+    if !signer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    signer_set.signer_index(signer.key).ok_or_else(|| {
+        msg!("Err: {} is not a multisig signer", signer.key);
+        DepositError::NotMultisigSigner.into()
+    })
+}
+
+fn load_proposal(
+    program_id: &Pubkey,
+    signer_set: &Multisig,
+    proposal: &AccountInfo,
+) -> Result<WithdrawalProposal, ProgramError> {
+    if proposal.owner != program_id || proposal.data_is_empty() {
+        return Err(DepositError::ProposalMismatch.into());
+    }
+
+    let pending = load::<WithdrawalProposal>(proposal)?;
+    let (expected, _) = find_proposal_address(program_id, &signer_set.owner, pending.id);
+    if pending.owner != signer_set.owner || *proposal.key != expected {
+        return Err(DepositError::ProposalMismatch.into());
+    }
+    Ok(pending)
+}
@@ -0,0 +1,138 @@
+use solana_deposit_interface::{
+    error::DepositError,
+    event::EventKind,
+    state::{Config, DepositAccount, REWARD_INDEX_SCALE},
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{
+    admin::load_admin_config,
+    events::emit,
+    utils::{check_vault, load_config, load_ledger, pay_from_vault, store, transfer_to_vault},
+};
+
+pub fn distribute_rewards(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let admin = next_account_info(accounts_iter)?;
+    let vault = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    let mut state = load_admin_config(program_id, admin, config)?;
+    let deposited = state.total_deposits.saturating_add(state.share_pool);
+    if deposited == 0 {
+        return Err(DepositError::NoDeposits.into());
+    }
+    check_vault(program_id, vault)?;
+
+    // The share pool's part stays in the pool, raising the value of every share.
+    let pooled = (amount as u128 * state.share_pool as u128 / deposited as u128) as u64;
+    state.share_pool = state
+        .share_pool
+        .checked_add(pooled)
+        .ok_or(DepositError::Overflow)?;
+    if state.total_deposits > 0 {
+        // Remainders below one index unit stay in the vault undistributed.
+        let increment =
+            (amount - pooled) as u128 * REWARD_INDEX_SCALE / state.total_deposits as u128;
+        state.reward_index = state
+            .reward_index
+            .checked_add(increment)
+            .ok_or(DepositError::Overflow)?;
+    }
+
+    transfer_to_vault(admin, vault, system_program, amount)?;
+    store(&state, config)?;
+
+    msg!(
+        "Rewards distributed: {} over {} deposited, {} to the share pool",
+        amount,
+        deposited,
+        pooled
+    );
+    emit(EventKind::DistributeRewards, admin.key, amount, deposited)
+}
+
+pub fn claim_rewards(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let user = next_account_info(accounts_iter)?;
+    let vault = next_account_info(accounts_iter)?;
+    let ledger = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+
+    if !user.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let state = load_config(program_id, config)?;
+    if state.withdrawals_blocked() {
+        msg!("Err: withdrawals are paused");
+        return Err(DepositError::Paused.into());
+    }
+
+    let mut deposit_account = load_ledger(program_id, user, ledger)?;
+    // The owner alone must not move what the signer set guards.
+    if deposit_account.multisig {
+        return Err(DepositError::MultisigRequired.into());
+    }
+    deposit_account.settle_rewards(state.reward_index);
+    let rewards = deposit_account.rewards;
+    deposit_account.rewards = 0;
+    store(&deposit_account, ledger)?;
+
+    if rewards > 0 {
+        pay_from_vault(program_id, vault, user, system_program, rewards)?;
+    }
+
+    msg!("Rewards claimed: {}", rewards);
+    emit(
+        EventKind::ClaimRewards,
+        user.key,
+        rewards,
+        deposit_account.balance,
+    )
+}
+
+/// Credits `amount` to a ledger, settling its rewards first.
+pub fn credit_ledger(
+    state: &mut Config,
+    deposit_account: &mut DepositAccount,
+    amount: u64,
+) -> ProgramResult {
+    deposit_account.settle_rewards(state.reward_index);
+    deposit_account.credit(amount)?;
+    // Multisig ledgers cannot claim rewards, so they stay out of the base.
+    if deposit_account.multisig {
+        return Ok(());
+    }
+    state.total_deposits = state
+        .total_deposits
+        .checked_add(amount)
+        .ok_or(DepositError::Overflow)?;
+    Ok(())
+}
+
+/// Debits `amount` from a ledger, settling its rewards first.
+pub fn debit_ledger(
+    state: &mut Config,
+    deposit_account: &mut DepositAccount,
+    amount: u64,
+) -> ProgramResult {
+    deposit_account.settle_rewards(state.reward_index);
+    deposit_account.debit(amount)?;
+    if !deposit_account.multisig {
+        state.total_deposits = state.total_deposits.saturating_sub(amount);
+    }
+    Ok(())
+}
@@ -0,0 +1,219 @@
+use solana_deposit_interface::{
+    error::DepositError, event::EventKind, find_share_mint_address, get_share_account_address,
+    state::Config, SHARE_MINT_SEED,
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+use spl_token::state::Mint;
+
+use crate::{
+    admin::load_admin_config,
+    events::emit,
+    fees::pay_withdrawal,
+    utils::{load_config, store},
+};
+
+/// Receipt shares use the same precision as lamports.
+const SHARE_DECIMALS: u8 = 9;
+
+pub fn create_share_mint(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let admin = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+    let share_mint = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    let mut state = load_admin_config(program_id, admin, config)?;
+    if state.shares_enabled {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    let bump = check_share_mint(program_id, share_mint, token_program)?;
+
+    let lamports = Rent::get()?.minimum_balance(Mint::LEN);
+    invoke_signed(
+        &system_instruction::create_account(
+            admin.key,
+            share_mint.key,
+            lamports,
+            Mint::LEN as u64,
+            token_program.key,
+        ),
+        &[admin.clone(), share_mint.clone(), system_program.clone()],
+        &[&[SHARE_MINT_SEED, &[bump]]],
+    )?;
+    invoke(
+        &spl_token::instruction::initialize_mint2(
+            token_program.key,
+            share_mint.key,
+            share_mint.key,
+            None,
+            SHARE_DECIMALS,
+        )?,
+        &[share_mint.clone(), token_program.clone()],
+    )?;
+
+    state.shares_enabled = true;
+    store(&state, config)?;
+
+    msg!("Share mint created: {}", share_mint.key);
+    emit(EventKind::CreateShareMint, admin.key, 0, 0)
+}
+
+/// Mints receipt shares for `amount` lamports deposited by `user` into their
+/// share token account, which follows in `accounts_iter`.
+pub fn mint_shares<'a, 'b: 'a>(
+    program_id: &Pubkey,
+    state: &mut Config,
+    user: &AccountInfo<'b>,
+    accounts_iter: &mut impl Iterator<Item = &'a AccountInfo<'b>>,
+    amount: u64,
+) -> ProgramResult {
+    let share_mint = next_account_info(accounts_iter)?;
+    let share_account = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+
+    let bump = check_share_mint(program_id, share_mint, token_program)?;
+    check_share_account(program_id, user, share_account)?;
+
+    let supply = Mint::unpack(&share_mint.try_borrow_data()?)?.supply;
+    let shares = state.shares_for_deposit(amount, supply);
+    state.share_pool = state
+        .share_pool
+        .checked_add(amount)
+        .ok_or(DepositError::Overflow)?;
+
+    invoke_signed(
+        &spl_token::instruction::mint_to(
+            token_program.key,
+            share_mint.key,
+            share_account.key,
+            share_mint.key,
+            &[],
+            shares,
+        )?,
+        &[
+            share_mint.clone(),
+            share_account.clone(),
+            token_program.clone(),
+        ],
+        &[&[SHARE_MINT_SEED, &[bump]]],
+    )?;
+
+    msg!("Shares minted: {}", shares);
+    Ok(())
+}
+
+pub fn redeem_shares(program_id: &Pubkey, accounts: &[AccountInfo], shares: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let holder = next_account_info(accounts_iter)?;
+    let vault = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+    let share_mint = next_account_info(accounts_iter)?;
+    let share_account = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+
+    if !holder.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if shares == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut state = load_config(program_id, config)?;
+    if state.withdrawals_blocked() {
+        msg!("Err: withdrawals are paused");
+        return Err(DepositError::Paused.into());
+    }
+    check_share_mint(program_id, share_mint, token_program)?;
+
+    let supply = Mint::unpack(&share_mint.try_borrow_data()?)?.supply;
+    let value = state.share_value(shares, supply);
+    state.share_pool = state
+        .share_pool
+        .checked_sub(value)
+        .ok_or(DepositError::Overflow)?;
+
+    // The token program only lets the account's owner or delegate burn.
+    invoke(
+        &spl_token::instruction::burn(
+            token_program.key,
+            share_account.key,
+            share_mint.key,
+            holder.key,
+            &[],
+            shares,
+        )?,
+        &[
+            share_account.clone(),
+            share_mint.clone(),
+            holder.clone(),
+            token_program.clone(),
+        ],
+    )?;
+    store(&state, config)?;
+
+    pay_withdrawal(
+        program_id,
+        &mut state,
+        config,
+        vault,
+        holder,
+        system_program,
+        value,
+    )?;
+
+    msg!("Shares redeemed: {} for {}", shares, value);
+    emit(EventKind::RedeemShares, holder.key, value, shares)
+}
+
+/// Ensures `share_account` is the associated share token account of `owner`.
+fn check_share_account(
+    program_id: &Pubkey,
+    owner: &AccountInfo,
+    share_account: &AccountInfo,
+) -> ProgramResult {
+    let expected = get_share_account_address(program_id, owner.key);
+    if *share_account.key != expected {
+        msg!(
+            "Err: share account mismatch: {} expected: {}",
+            share_account.key,
+            expected
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(())
+}
+
+/// Ensures `share_mint` is the share mint PDA and returns its bump seed.
+fn check_share_mint(
+    program_id: &Pubkey,
+    share_mint: &AccountInfo,
+    token_program: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    if *token_program.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let (expected, bump) = find_share_mint_address(program_id);
+    if *share_mint.key != expected {
+        msg!(
+            "Err: share mint mismatch: {} expected: {}",
+            share_mint.key,
+            expected
+        );
+        return Err(DepositError::ShareMintMismatch.into());
+    }
+    Ok(bump)
+}
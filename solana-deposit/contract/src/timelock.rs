@@ -0,0 +1,213 @@
+use solana_deposit_interface::{
+    error::DepositError,
+    event::EventKind,
+    find_withdrawal_address,
+    state::{DepositAccount, PendingWithdrawal},
+    WITHDRAWAL_SEED,
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+use crate::{
+    events::emit,
+    fees::pay_withdrawal,
+    rewards::{credit_ledger, debit_ledger},
+    utils::{close_account, create_pda_account, load, load_config, load_ledger, store},
+};
+
+pub fn request_withdraw(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let user = next_account_info(accounts_iter)?;
+    let ledger = next_account_info(accounts_iter)?;
+    let withdrawal = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+
+    if !user.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut state = load_config(program_id, config)?;
+    if state.withdrawals_blocked() {
+        msg!("Err: withdrawals are paused");
+        return Err(DepositError::Paused.into());
+    }
+
+    let mut deposit_account = load_ledger(program_id, user, ledger)?;
+    if deposit_account.multisig {
+        return Err(DepositError::MultisigRequired.into());
+    }
+    let id = deposit_account.next_withdrawal_id;
+    let (expected, bump) = find_withdrawal_address(program_id, user.key, id);
+    if *withdrawal.key != expected {
+        msg!(
+            "Err: withdrawal mismatch: {} expected: {}",
+            withdrawal.key,
+            expected
+        );
+        return Err(DepositError::WithdrawalMismatch.into());
+    }
+
+    debit_ledger(&mut state, &mut deposit_account, amount)?;
+    deposit_account.next_withdrawal_id = id.checked_add(1).ok_or(DepositError::Overflow)?;
+    deposit_account.pending_withdrawals = deposit_account
+        .pending_withdrawals
+        .checked_add(1)
+        .ok_or(DepositError::Overflow)?;
+    store(&deposit_account, ledger)?;
+    store(&state, config)?;
+
+    create_pda_account(
+        program_id,
+        user,
+        withdrawal,
+        system_program,
+        PendingWithdrawal::LEN,
+        &[
+            WITHDRAWAL_SEED,
+            user.key.as_ref(),
+            &id.to_le_bytes(),
+            &[bump],
+        ],
+    )?;
+
+    let unlock_at = Clock::get()?
+        .unix_timestamp
+        .checked_add(state.withdraw_delay)
+        .ok_or(DepositError::Overflow)?;
+    let pending = PendingWithdrawal {
+        user: *user.key,
+        id,
+        amount,
+        unlock_at,
+    };
+    store(&pending, withdrawal)?;
+
+    msg!("Withdrawal {} of {} unlocks at {}", id, amount, unlock_at);
+    emit(
+        EventKind::RequestWithdraw,
+        user.key,
+        amount,
+        deposit_account.balance,
+    )
+}
+
+pub fn claim_withdraw(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let user = next_account_info(accounts_iter)?;
+    let vault = next_account_info(accounts_iter)?;
+    let ledger = next_account_info(accounts_iter)?;
+    let withdrawal = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+
+    if !user.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut state = load_config(program_id, config)?;
+    if state.withdrawals_blocked() {
+        msg!("Err: withdrawals are paused");
+        return Err(DepositError::Paused.into());
+    }
+
+    let pending = load_pending(program_id, user, withdrawal)?;
+    let now = Clock::get()?.unix_timestamp;
+    if now < pending.unlock_at {
+        msg!(
+            "Err: withdrawal {} unlocks at {}",
+            pending.id,
+            pending.unlock_at
+        );
+        return Err(DepositError::WithdrawalLocked.into());
+    }
+
+    let mut deposit_account = load_ledger(program_id, user, ledger)?;
+    pay_withdrawal(
+        program_id,
+        &mut state,
+        config,
+        vault,
+        user,
+        system_program,
+        pending.amount,
+    )?;
+
+    release_pending(&mut deposit_account, withdrawal, user)?;
+    store(&deposit_account, ledger)?;
+
+    msg!("Withdrawal {} claimed", pending.id);
+    emit(
+        EventKind::ClaimWithdraw,
+        user.key,
+        pending.amount,
+        deposit_account.balance,
+    )
+}
+
+pub fn cancel_withdraw(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let user = next_account_info(accounts_iter)?;
+    let ledger = next_account_info(accounts_iter)?;
+    let withdrawal = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+
+    if !user.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut state = load_config(program_id, config)?;
+    let pending = load_pending(program_id, user, withdrawal)?;
+
+    let mut deposit_account = load_ledger(program_id, user, ledger)?;
+    credit_ledger(&mut state, &mut deposit_account, pending.amount)?;
+    release_pending(&mut deposit_account, withdrawal, user)?;
+    store(&deposit_account, ledger)?;
+    store(&state, config)?;
+
+    msg!("Withdrawal {} cancelled", pending.id);
+    emit(
+        EventKind::CancelWithdraw,
+        user.key,
+        pending.amount,
+        deposit_account.balance,
+    )
+}
+
+fn load_pending(
+    program_id: &Pubkey,
+    user: &AccountInfo,
+    withdrawal: &AccountInfo,
+) -> Result<PendingWithdrawal, ProgramError> {
+    if withdrawal.owner != program_id || withdrawal.data_is_empty() {
+        return Err(DepositError::WithdrawalMismatch.into());
+    }
+
+    let pending = load::<PendingWithdrawal>(withdrawal)?;
+    let (expected, _) = find_withdrawal_address(program_id, user.key, pending.id);
+    if pending.user != *user.key || *withdrawal.key != expected {
+        return Err(DepositError::WithdrawalMismatch.into());
+    }
+    Ok(pending)
+}
+
+/// Drops a pending withdrawal from the ledger's count and closes its account.
+fn release_pending(
+    deposit_account: &mut DepositAccount,
+    withdrawal: &AccountInfo,
+    user: &AccountInfo,
+) -> ProgramResult {
+    deposit_account.pending_withdrawals = deposit_account.pending_withdrawals.saturating_sub(1);
+    close_account(withdrawal, user)
+}
@@ -0,0 +1,231 @@
+use solana_deposit_interface::{
+    error::DepositError, event::EventKind, find_token_ledger_address, get_token_vault_address,
+    state::TokenDepositAccount, TOKEN_LEDGER_SEED, VAULT_SEED,
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+};
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use spl_token::state::Mint;
+
+use crate::{
+    allowlist::check_allowlisted,
+    events::emit,
+    utils::{check_vault, create_pda_account, load, load_config, store},
+};
+
+pub fn deposit_token(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let user = next_account_info(accounts_iter)?;
+    let user_token = next_account_info(accounts_iter)?;
+    let vault_token = next_account_info(accounts_iter)?;
+    let token_ledger = next_account_info(accounts_iter)?;
+    let mint = next_account_info(accounts_iter)?;
+    let vault = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+    let ata_program = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    if !user.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let state = load_config(program_id, config)?;
+    if state.paused {
+        msg!("Err: deposits are paused");
+        return Err(DepositError::Paused.into());
+    }
+    check_allowlisted(program_id, &state, user, accounts_iter)?;
+
+    check_vault(program_id, vault)?;
+    check_token_accounts(program_id, vault_token, mint, token_program)?;
+    let ledger_bump = check_token_ledger(program_id, user, mint, token_ledger)?;
+
+    if vault_token.data_is_empty() {
+        invoke(
+            &create_associated_token_account_idempotent(
+                user.key,
+                vault.key,
+                mint.key,
+                token_program.key,
+            ),
+            &[
+                user.clone(),
+                vault_token.clone(),
+                vault.clone(),
+                mint.clone(),
+                system_program.clone(),
+                token_program.clone(),
+                ata_program.clone(),
+            ],
+        )?;
+        msg!("Token vault created: {}", vault_token.key);
+    }
+
+    let mut token_account = if token_ledger.data_is_empty() {
+        create_pda_account(
+            program_id,
+            user,
+            token_ledger,
+            system_program,
+            TokenDepositAccount::LEN,
+            &[
+                TOKEN_LEDGER_SEED,
+                user.key.as_ref(),
+                mint.key.as_ref(),
+                &[ledger_bump],
+            ],
+        )?;
+        TokenDepositAccount {
+            user: *user.key,
+            mint: *mint.key,
+            balance: 0,
+        }
+    } else if token_ledger.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    } else {
+        load::<TokenDepositAccount>(token_ledger)?
+    };
+    token_account.credit(amount)?;
+
+    let decimals = Mint::unpack(&mint.try_borrow_data()?)?.decimals;
+    invoke(
+        &spl_token::instruction::transfer_checked(
+            token_program.key,
+            user_token.key,
+            mint.key,
+            vault_token.key,
+            user.key,
+            &[],
+            amount,
+            decimals,
+        )?,
+        &[
+            user_token.clone(),
+            mint.clone(),
+            vault_token.clone(),
+            user.clone(),
+            token_program.clone(),
+        ],
+    )?;
+
+    store(&token_account, token_ledger)?;
+
+    msg!("Token deposit: {} of {}", amount, mint.key);
+    emit(
+        EventKind::DepositToken,
+        user.key,
+        amount,
+        token_account.balance,
+    )
+}
+
+pub fn withdraw_token(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let user = next_account_info(accounts_iter)?;
+    let user_token = next_account_info(accounts_iter)?;
+    let vault_token = next_account_info(accounts_iter)?;
+    let token_ledger = next_account_info(accounts_iter)?;
+    let mint = next_account_info(accounts_iter)?;
+    let vault = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+
+    if !user.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if load_config(program_id, config)?.withdrawals_blocked() {
+        msg!("Err: withdrawals are paused");
+        return Err(DepositError::Paused.into());
+    }
+
+    let vault_bump = check_vault(program_id, vault)?;
+    check_token_accounts(program_id, vault_token, mint, token_program)?;
+    check_token_ledger(program_id, user, mint, token_ledger)?;
+    if token_ledger.owner != program_id || token_ledger.data_is_empty() {
+        return Err(DepositError::LedgerNotFound.into());
+    }
+
+    let mut token_account = load::<TokenDepositAccount>(token_ledger)?;
+    token_account.debit(amount)?;
+    store(&token_account, token_ledger)?;
+
+    let decimals = Mint::unpack(&mint.try_borrow_data()?)?.decimals;
+    invoke_signed(
+        &spl_token::instruction::transfer_checked(
+            token_program.key,
+            vault_token.key,
+            mint.key,
+            user_token.key,
+            vault.key,
+            &[],
+            amount,
+            decimals,
+        )?,
+        &[
+            vault_token.clone(),
+            mint.clone(),
+            user_token.clone(),
+            vault.clone(),
+            token_program.clone(),
+        ],
+        &[&[VAULT_SEED, &[vault_bump]]],
+    )?;
+
+    msg!("Token withdrawal: {} of {}", amount, mint.key);
+    emit(
+        EventKind::WithdrawToken,
+        user.key,
+        amount,
+        token_account.balance,
+    )
+}
+
+fn check_token_accounts(
+    program_id: &Pubkey,
+    vault_token: &AccountInfo,
+    mint: &AccountInfo,
+    token_program: &AccountInfo,
+) -> ProgramResult {
+    if *token_program.key != spl_token::id() || *mint.owner != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let expected = get_token_vault_address(program_id, mint.key);
+    if *vault_token.key != expected {
+        msg!(
+            "Err: token vault mismatch: {} expected: {}",
+            vault_token.key,
+            expected
+        );
+        return Err(DepositError::TokenVaultMismatch.into());
+    }
+    Ok(())
+}
+
+/// Ensures `token_ledger` is the ledger PDA of `user` for `mint` and returns its bump seed.
+fn check_token_ledger(
+    program_id: &Pubkey,
+    user: &AccountInfo,
+    mint: &AccountInfo,
+    token_ledger: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    let (expected, bump) = find_token_ledger_address(program_id, user.key, mint.key);
+    if *token_ledger.key != expected {
+        msg!(
+            "Err: token ledger mismatch: {} expected: {}",
+            token_ledger.key,
+            expected
+        );
+        return Err(DepositError::TokenLedgerMismatch.into());
+    }
+    Ok(bump)
+}
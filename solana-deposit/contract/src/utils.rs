@@ -0,0 +1,174 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_deposit_interface::{
+    error::DepositError,
+    find_config_address, find_ledger_address, find_vault_address,
+    state::{Config, DepositAccount},
+    VAULT_SEED,
+};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+
+/// Ensures `vault` is the program vault PDA and returns its bump seed.
+pub fn check_vault(program_id: &Pubkey, vault: &AccountInfo) -> Result<u8, ProgramError> {
+    let (expected, bump) = find_vault_address(program_id);
+    if *vault.key != expected {
+        msg!("Err: vault mismatch: {} expected: {}", vault.key, expected);
+        return Err(DepositError::VaultMismatch.into());
+    }
+    Ok(bump)
+}
+
+/// Ensures `ledger` is the ledger PDA of `user` and returns its bump seed.
+pub fn check_ledger(
+    program_id: &Pubkey,
+    user: &AccountInfo,
+    ledger: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    let (expected, bump) = find_ledger_address(program_id, user.key);
+    if *ledger.key != expected {
+        msg!(
+            "Err: ledger mismatch: {} expected: {}",
+            ledger.key,
+            expected
+        );
+        return Err(DepositError::LedgerMismatch.into());
+    }
+    Ok(bump)
+}
+
+/// Loads the existing ledger of `user` after checking its address and owner.
+pub fn load_ledger(
+    program_id: &Pubkey,
+    user: &AccountInfo,
+    ledger: &AccountInfo,
+) -> Result<DepositAccount, ProgramError> {
+    check_ledger(program_id, user, ledger)?;
+    if ledger.owner != program_id || ledger.data_is_empty() {
+        return Err(DepositError::LedgerNotFound.into());
+    }
+    load(ledger)
+}
+
+/// Ensures `config` is the program config PDA and returns its bump seed.
+pub fn check_config(program_id: &Pubkey, config: &AccountInfo) -> Result<u8, ProgramError> {
+    let (expected, bump) = find_config_address(program_id);
+    if *config.key != expected {
+        msg!(
+            "Err: config mismatch: {} expected: {}",
+            config.key,
+            expected
+        );
+        return Err(DepositError::ConfigMismatch.into());
+    }
+    Ok(bump)
+}
+
+/// Loads the program config after checking the account is the initialized config PDA.
+pub fn load_config(program_id: &Pubkey, config: &AccountInfo) -> Result<Config, ProgramError> {
+    check_config(program_id, config)?;
+    if config.owner != program_id || config.data_is_empty() {
+        return Err(DepositError::NotInitialized.into());
+    }
+    load(config)
+}
+
+/// Creates a rent-exempt, program-owned account at the PDA derived from `seeds`.
+pub fn create_pda_account<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    space: usize,
+    seeds: &[&[u8]],
+) -> ProgramResult {
+    let lamports = Rent::get()?.minimum_balance(space);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            account.key,
+            lamports,
+            space as u64,
+            program_id,
+        ),
+        &[payer.clone(), account.clone(), system_program.clone()],
+        &[seeds],
+    )
+}
+
+/// Reads program state from `account`, tolerating trailing space.
+pub fn load<T: BorshDeserialize>(account: &AccountInfo) -> Result<T, ProgramError> {
+    let data = account.try_borrow_data()?;
+    Ok(T::deserialize(&mut &data[..])?)
+}
+
+pub fn store<T: BorshSerialize>(value: &T, account: &AccountInfo) -> ProgramResult {
+    value.serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
+
+/// Moves `amount` lamports from the signing `payer` into the vault PDA.
+pub fn transfer_to_vault<'a>(
+    payer: &AccountInfo<'a>,
+    vault: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    amount: u64,
+) -> ProgramResult {
+    invoke(
+        &system_instruction::transfer(payer.key, vault.key, amount),
+        &[payer.clone(), vault.clone(), system_program.clone()],
+    )
+}
+
+/// Pays `amount` lamports out of the vault PDA, signing with its seeds.
+///
+/// The rent-exempt reserve funded by `Initialize` is never paid out.
+pub fn pay_from_vault<'a>(
+    program_id: &Pubkey,
+    vault: &AccountInfo<'a>,
+    recipient: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    amount: u64,
+) -> ProgramResult {
+    let bump = check_vault(program_id, vault)?;
+    let reserve = Rent::get()?.minimum_balance(0);
+    if vault.lamports().saturating_sub(amount) < reserve {
+        msg!(
+            "Err: vault holds {} need {} above reserve {}",
+            vault.lamports(),
+            amount,
+            reserve
+        );
+        return Err(ProgramError::InsufficientFunds);
+    }
+
+    invoke_signed(
+        &system_instruction::transfer(vault.key, recipient.key, amount),
+        &[vault.clone(), recipient.clone(), system_program.clone()],
+        &[&[VAULT_SEED, &[bump]]],
+    )
+}
+
+/// Closes a program-owned account, returning its rent lamports to `destination`.
+///
+/// Call this after any CPI in the same instruction; the native
+/// solana-program-test runtime rejects CPIs that follow direct lamport edits.
+pub fn close_account(account: &AccountInfo, destination: &AccountInfo) -> ProgramResult {
+    let lamports = account.lamports();
+    **destination.try_borrow_mut_lamports()? = destination
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(DepositError::Overflow)?;
+    **account.try_borrow_mut_lamports()? = 0;
+    account.try_borrow_mut_data()?.fill(0);
+    Ok(())
+}
@@ -0,0 +1,195 @@
+use solana_deposit_interface::{
+    error::DepositError, event::EventKind, find_vesting_address, state::VestingSchedule,
+    VESTING_SEED,
+};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+use crate::{
+    allowlist::check_allowlisted,
+    events::emit,
+    fees::{accrue_fee, pay_withdrawal},
+    limits::check_deposit_limits,
+    utils::{
+        check_vault, close_account, create_pda_account, load, load_config, store, transfer_to_vault,
+    },
+};
+
+pub fn deposit_vested(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+    beneficiary: Pubkey,
+    start: i64,
+    cliff: i64,
+    end: i64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let funder = next_account_info(accounts_iter)?;
+    let vault = next_account_info(accounts_iter)?;
+    let schedule = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+
+    if !funder.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut state = load_config(program_id, config)?;
+    if state.paused {
+        msg!("Err: deposits are paused");
+        return Err(DepositError::Paused.into());
+    }
+    check_allowlisted(program_id, &state, funder, accounts_iter)?;
+
+    check_vault(program_id, vault)?;
+    check_deposit_limits(&state, amount)?;
+    let (expected, bump) = find_vesting_address(program_id, funder.key, &beneficiary);
+    if *schedule.key != expected {
+        msg!(
+            "Err: vesting mismatch: {} expected: {}",
+            schedule.key,
+            expected
+        );
+        return Err(DepositError::VestingMismatch.into());
+    }
+    if !schedule.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let fee = state.deposit_fee(amount);
+    let total = accrue_fee(&mut state, amount, fee)?;
+    state.total_locked = state
+        .total_locked
+        .checked_add(total)
+        .ok_or(DepositError::Overflow)?;
+    let grant = VestingSchedule {
+        funder: *funder.key,
+        beneficiary,
+        total,
+        withdrawn: 0,
+        start,
+        cliff,
+        end,
+    };
+    if !grant.is_valid() {
+        msg!(
+            "Err: invalid schedule start {} cliff {} end {}",
+            start,
+            cliff,
+            end
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    create_pda_account(
+        program_id,
+        funder,
+        schedule,
+        system_program,
+        VestingSchedule::LEN,
+        &[
+            VESTING_SEED,
+            funder.key.as_ref(),
+            beneficiary.as_ref(),
+            &[bump],
+        ],
+    )?;
+
+    transfer_to_vault(funder, vault, system_program, amount)?;
+
+    store(&grant, schedule)?;
+    store(&state, config)?;
+    if fee > 0 {
+        msg!("Deposit fee: {}", fee);
+    }
+
+    msg!(
+        "Vesting {} for {} from {} cliff {} until {}",
+        total,
+        beneficiary,
+        start,
+        cliff,
+        end
+    );
+    emit(EventKind::DepositVested, &beneficiary, amount, total)
+}
+
+pub fn withdraw_vested(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let beneficiary = next_account_info(accounts_iter)?;
+    let funder = next_account_info(accounts_iter)?;
+    let vault = next_account_info(accounts_iter)?;
+    let schedule = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let config = next_account_info(accounts_iter)?;
+
+    if !beneficiary.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut state = load_config(program_id, config)?;
+    if state.withdrawals_blocked() {
+        msg!("Err: withdrawals are paused");
+        return Err(DepositError::Paused.into());
+    }
+
+    let (expected, _) = find_vesting_address(program_id, funder.key, beneficiary.key);
+    if *schedule.key != expected || schedule.owner != program_id || schedule.data_is_empty() {
+        return Err(DepositError::VestingMismatch.into());
+    }
+
+    let mut grant = load::<VestingSchedule>(schedule)?;
+    let now = Clock::get()?.unix_timestamp;
+    let available = grant.withdrawable(now);
+    if amount > available {
+        msg!("Err: vested available: {} need: {}", available, amount);
+        return Err(DepositError::VestingLocked.into());
+    }
+    grant.withdrawn = grant
+        .withdrawn
+        .checked_add(amount)
+        .ok_or(DepositError::Overflow)?;
+    store(&grant, schedule)?;
+    state.total_locked = state.total_locked.saturating_sub(amount);
+    store(&state, config)?;
+
+    pay_withdrawal(
+        program_id,
+        &mut state,
+        config,
+        vault,
+        beneficiary,
+        system_program,
+        amount,
+    )?;
+
+    if grant.withdrawn == grant.total {
+        close_account(schedule, funder)?;
+        msg!("Vesting schedule completed");
+    }
+
+    msg!(
+        "Vested withdrawal: {} ({} of {} withdrawn)",
+        amount,
+        grant.withdrawn,
+        grant.total
+    );
+    emit(
+        EventKind::WithdrawVested,
+        beneficiary.key,
+        amount,
+        grant.total - grant.withdrawn,
+    )
+}
@@ -0,0 +1,131 @@
+mod common;
+
+use common::{custom, instruction_error, setup, setup_uninitialized};
+use solana_deposit_interface::{error::DepositError, find_config_address, instruction};
+use solana_sdk::{
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+#[tokio::test]
+async fn initialize_records_admin_once() {
+    let mut h = setup().await;
+
+    let config = h.config().await;
+    assert_eq!(config.admin, h.payer.pubkey());
+    assert_eq!(config.pending_admin, Pubkey::default());
+
+    h.advance_clock(0).await;
+    let ix = instruction::initialize(&h.program_id, &h.payer.pubkey());
+    let err = instruction_error(h.process(&[ix], &[]).await);
+    assert_eq!(err, custom(DepositError::AlreadyInitialized));
+}
+
+#[tokio::test]
+async fn initialize_requires_upgrade_authority() {
+    let mut h = setup_uninitialized().await;
+    let stranger = h.user.insecure_clone();
+
+    let ix = instruction::initialize(&h.program_id, &stranger.pubkey());
+    let err = instruction_error(h.process(&[ix], &[&stranger]).await);
+    assert_eq!(err, custom(DepositError::Unauthorized));
+    let config = find_config_address(&h.program_id).0;
+    assert!(h.banks.get_account(config).await.unwrap().is_none());
+
+    h.initialize().await;
+    assert_eq!(h.config().await.admin, h.payer.pubkey());
+}
+
+#[tokio::test]
+async fn authority_transfer_takes_two_steps() {
+    let mut h = setup().await;
+    let admin = h.payer.pubkey();
+    let new_admin = h.user.insecure_clone();
+
+    let ix = instruction::set_authority(&h.program_id, &admin, &new_admin.pubkey());
+    h.process(&[ix], &[]).await.unwrap();
+    assert_eq!(h.config().await.admin, admin);
+    assert_eq!(h.config().await.pending_admin, new_admin.pubkey());
+
+    let ix = instruction::accept_authority(&h.program_id, &new_admin.pubkey());
+    h.process(&[ix], &[&new_admin]).await.unwrap();
+
+    let config = h.config().await;
+    assert_eq!(config.admin, new_admin.pubkey());
+    assert_eq!(config.pending_admin, Pubkey::default());
+
+    let ix = instruction::set_authority(&h.program_id, &admin, &admin);
+    let err = instruction_error(h.process(&[ix], &[]).await);
+    assert_eq!(err, custom(DepositError::Unauthorized));
+}
+
+#[tokio::test]
+async fn only_admin_can_nominate_and_only_nominee_can_accept() {
+    let mut h = setup().await;
+    let user = h.user.insecure_clone();
+
+    let ix = instruction::set_authority(&h.program_id, &user.pubkey(), &user.pubkey());
+    let err = instruction_error(h.process(&[ix], &[&user]).await);
+    assert_eq!(err, custom(DepositError::Unauthorized));
+
+    let nominee = Keypair::new();
+    let ix = instruction::set_authority(&h.program_id, &h.payer.pubkey(), &nominee.pubkey());
+    h.process(&[ix], &[]).await.unwrap();
+
+    let ix = instruction::accept_authority(&h.program_id, &user.pubkey());
+    let err = instruction_error(h.process(&[ix], &[&user]).await);
+    assert_eq!(err, custom(DepositError::Unauthorized));
+}
+
+#[tokio::test]
+async fn admin_instructions_require_initialized_config() {
+    let mut h = setup_uninitialized().await;
+
+    let ix = instruction::set_authority(&h.program_id, &h.payer.pubkey(), &Pubkey::new_unique());
+    let err = instruction_error(h.process(&[ix], &[]).await);
+    assert_eq!(err, custom(DepositError::NotInitialized));
+}
+
+#[tokio::test]
+async fn deposits_require_initialized_config() {
+    let mut h = setup_uninitialized().await;
+
+    let err = instruction_error(h.deposit(LAMPORTS_PER_SOL).await);
+    assert_eq!(err, custom(DepositError::NotInitialized));
+}
+
+#[tokio::test]
+async fn pause_blocks_deposits_and_optionally_withdrawals() {
+    let mut h = setup().await;
+    let admin = h.payer.pubkey();
+    h.deposit(2 * LAMPORTS_PER_SOL).await.unwrap();
+
+    let ix = instruction::set_paused(&h.program_id, &admin, true, true);
+    h.process(&[ix], &[]).await.unwrap();
+    let err = instruction_error(h.deposit(LAMPORTS_PER_SOL).await);
+    assert_eq!(err, custom(DepositError::Paused));
+    h.withdraw(LAMPORTS_PER_SOL / 2).await.unwrap();
+
+    let ix = instruction::set_paused(&h.program_id, &admin, true, false);
+    h.process(&[ix], &[]).await.unwrap();
+    let err = instruction_error(h.withdraw(LAMPORTS_PER_SOL).await);
+    assert_eq!(err, custom(DepositError::Paused));
+
+    let ix = instruction::set_paused(&h.program_id, &admin, false, false);
+    h.process(&[ix], &[]).await.unwrap();
+    h.deposit(LAMPORTS_PER_SOL).await.unwrap();
+    h.withdraw(LAMPORTS_PER_SOL).await.unwrap();
+    assert_eq!(h.ledger_balance().await, 3 * LAMPORTS_PER_SOL / 2);
+}
+
+#[tokio::test]
+async fn only_admin_can_pause() {
+    let mut h = setup().await;
+    let user = h.user.insecure_clone();
+
+    let ix = instruction::set_paused(&h.program_id, &user.pubkey(), true, false);
+    let err = instruction_error(h.process(&[ix], &[&user]).await);
+    assert_eq!(err, custom(DepositError::Unauthorized));
+    assert!(!h.config().await.paused);
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::{custom, instruction_error, setup, Harness};
+use solana_deposit_interface::{error::DepositError, find_allowlist_address, instruction};
+use solana_sdk::{
+    native_token::LAMPORTS_PER_SOL,
+    signature::{Keypair, Signer},
+};
+
+async fn setup_allowlist() -> Harness {
+    let mut h = setup().await;
+    let ix = instruction::set_allowlist_enabled(&h.program_id, &h.payer.pubkey(), true);
+    h.process(&[ix], &[]).await.unwrap();
+    h
+}
+
+#[tokio::test]
+async fn only_allowlisted_wallets_can_deposit() {
+    let mut h = setup_allowlist().await;
+    let admin = h.payer.pubkey();
+    let user = h.user.pubkey();
+
+    let err = instruction_error(h.deposit(LAMPORTS_PER_SOL).await);
+    assert_eq!(err, custom(DepositError::NotAllowlisted));
+
+    let ix = instruction::allowlist_add(&h.program_id, &admin, &user);
+    h.process(&[ix], &[]).await.unwrap();
+    h.deposit(LAMPORTS_PER_SOL).await.unwrap();
+
+    let ix = instruction::allowlist_remove(&h.program_id, &admin, &user);
+    h.process(&[ix], &[]).await.unwrap();
+    let err = instruction_error(h.deposit(2 * LAMPORTS_PER_SOL).await);
+    assert_eq!(err, custom(DepositError::NotAllowlisted));
+
+    h.withdraw(LAMPORTS_PER_SOL).await.unwrap();
+}
+
+#[tokio::test]
+async fn wrong_marker_is_rejected() {
+    let mut h = setup_allowlist().await;
+    let admin = h.payer.pubkey();
+    let user = h.user.pubkey();
+    let wrong = find_allowlist_address(&h.program_id, &admin).0;
+
+    let mut ix = instruction::allowlist_add(&h.program_id, &admin, &user);
+    ix.accounts[2].pubkey = wrong;
+    let err = instruction_error(h.process(&[ix], &[]).await);
+    assert_eq!(err, custom(DepositError::AllowlistMismatch));
+
+    let ix = instruction::allowlist_add(&h.program_id, &admin, &admin);
+    h.process(&[ix], &[]).await.unwrap();
+    let mut ix = instruction::allowlist_remove(&h.program_id, &admin, &user);
+    ix.accounts[2].pubkey = wrong;
+    let err = instruction_error(h.process(&[ix], &[]).await);
+    assert_eq!(err, custom(DepositError::AllowlistMismatch));
+}
+
+#[tokio::test]
+async fn escrows_are_gated_by_the_allowlist() {
+    let mut h = setup_allowlist().await;
+    let user = h.user.insecure_clone();
+
+    let ix = instruction::escrow_create(
+        &h.program_id,
+        &user.pubkey(),
+        0,
+        LAMPORTS_PER_SOL,
+        &Keypair::new().pubkey(),
+        60,
+    );
+    let err = instruction_error(h.process(&[ix], &[&user]).await);
+    assert_eq!(err, custom(DepositError::NotAllowlisted));
+}
+
+#[tokio::test]
+async fn disabled_allowlist_lets_anyone_deposit() {
+    let mut h = setup_allowlist().await;
+    let ix = instruction::set_allowlist_enabled(&h.program_id, &h.payer.pubkey(), false);
+    h.process(&[ix], &[]).await.unwrap();
+
+    h.deposit(LAMPORTS_PER_SOL).await.unwrap();
+    assert_eq!(h.ledger_balance().await, LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn only_admin_manages_the_allowlist() {
+    let mut h = setup_allowlist().await;
+    let user = h.user.insecure_clone();
+
+    let ix = instruction::allowlist_add(&h.program_id, &user.pubkey(), &user.pubkey());
+    let err = instruction_error(h.process(&[ix], &[&user]).await);
+    assert_eq!(err, custom(DepositError::Unauthorized));
+}
@@ -0,0 +1,162 @@
+mod common;
+
+use common::{custom, instruction_error, setup, Harness};
+use solana_deposit_interface::{
+    error::DepositError, find_delegation_address, find_ledger_address, instruction,
+};
+use solana_sdk::{
+    native_token::LAMPORTS_PER_SOL,
+    signature::{Keypair, Signer},
+};
+
+async fn close(
+    h: &mut Harness,
+    delegates: &[Keypair],
+) -> Result<(), solana_program_test::BanksClientError> {
+    let user = h.user.insecure_clone();
+    let delegates: Vec<_> = delegates.iter().map(|d| d.pubkey()).collect();
+    let ix = instruction::close_account(&h.program_id, &user.pubkey(), &[], &delegates);
+    h.process(&[ix], &[&user]).await
+}
+
+#[tokio::test]
+async fn close_returns_balance_and_rent() {
+    let mut h = setup().await;
+    let user = h.user.pubkey();
+    h.deposit(2 * LAMPORTS_PER_SOL).await.unwrap();
+
+    let ledger = find_ledger_address(&h.program_id, &user).0;
+    let rent = h.lamports(ledger).await;
+    let before = h.lamports(user).await;
+    close(&mut h, &[]).await.unwrap();
+
+    assert_eq!(h.lamports(user).await, before + 2 * LAMPORTS_PER_SOL + rent);
+    assert!(h.banks.get_account(ledger).await.unwrap().is_none());
+
+    h.deposit(LAMPORTS_PER_SOL).await.unwrap();
+    assert_eq!(h.ledger_balance().await, LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn close_needs_no_share_accounts_without_shares() {
+    let mut h = setup().await;
+    let user = h.user.insecure_clone();
+    h.deposit(LAMPORTS_PER_SOL).await.unwrap();
+
+    let ix = instruction::close_account(&h.program_id, &user.pubkey(), &[], &[]);
+    assert_eq!(ix.accounts.len(), 5);
+    h.process(&[ix], &[&user]).await.unwrap();
+    let ledger = find_ledger_address(&h.program_id, &user.pubkey()).0;
+    assert!(h.banks.get_account(ledger).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn close_also_closes_delegations() {
+    let mut h = setup().await;
+    let user = h.user.insecure_clone();
+    let delegate = Keypair::new();
+    h.deposit(LAMPORTS_PER_SOL).await.unwrap();
+    let ix = instruction::approve_delegate(
+        &h.program_id,
+        &user.pubkey(),
+        &delegate.pubkey(),
+        LAMPORTS_PER_SOL,
+        0,
+    );
+    h.process(&[ix], &[&user]).await.unwrap();
+
+    close(&mut h, &[delegate.insecure_clone()]).await.unwrap();
+    let delegation = find_delegation_address(&h.program_id, &user.pubkey(), &delegate.pubkey()).0;
+    assert!(h.banks.get_account(delegation).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn close_requires_every_delegation() {
+    let mut h = setup().await;
+    let user = h.user.insecure_clone();
+    let (listed, forgotten) = (Keypair::new(), Keypair::new());
+    h.deposit(LAMPORTS_PER_SOL).await.unwrap();
+    for delegate in [&listed, &forgotten] {
+        let ix = instruction::approve_delegate(
+            &h.program_id,
+            &user.pubkey(),
+            &delegate.pubkey(),
+            LAMPORTS_PER_SOL,
+            0,
+        );
+        h.process(&[ix], &[&user]).await.unwrap();
+    }
+    assert_eq!(h.ledger(&user.pubkey()).await.delegations, 2);
+
+    let err = instruction_error(close(&mut h, &[listed.insecure_clone()]).await);
+    assert_eq!(err, custom(DepositError::DelegationsOpen));
+
+    let ix = instruction::revoke_delegate(&h.program_id, &user.pubkey(), &forgotten.pubkey());
+    h.process(&[ix], &[&user]).await.unwrap();
+    assert_eq!(h.ledger(&user.pubkey()).await.delegations, 1);
+    close(&mut h, &[listed.insecure_clone()]).await.unwrap();
+}
+
+#[tokio::test]
+async fn old_delegate_cannot_spend_a_reopened_ledger() {
+    let mut h = setup().await;
+    let user = h.user.insecure_clone();
+    let delegate = Keypair::new();
+    h.deposit(LAMPORTS_PER_SOL).await.unwrap();
+    let ix = instruction::approve_delegate(
+        &h.program_id,
+        &user.pubkey(),
+        &delegate.pubkey(),
+        LAMPORTS_PER_SOL,
+        0,
+    );
+    h.process(&[ix], &[&user]).await.unwrap();
+    close(&mut h, &[delegate.insecure_clone()]).await.unwrap();
+
+    h.deposit(2 * LAMPORTS_PER_SOL).await.unwrap();
+    assert_eq!(h.ledger(&user.pubkey()).await.delegations, 0);
+    let ix = instruction::withdraw_delegated(
+        &h.program_id,
+        &delegate.pubkey(),
+        &user.pubkey(),
+        LAMPORTS_PER_SOL,
+    );
+    let err = instruction_error(h.process(&[ix], &[&delegate]).await);
+    assert_eq!(err, custom(DepositError::DelegationMismatch));
+}
+
+#[tokio::test]
+async fn close_is_refused_while_withdrawals_are_pending() {
+    let mut h = setup().await;
+    let user = h.user.insecure_clone();
+    let ix = instruction::set_withdraw_delay(&h.program_id, &h.payer.pubkey(), 60);
+    h.process(&[ix], &[]).await.unwrap();
+    h.deposit(2 * LAMPORTS_PER_SOL).await.unwrap();
+
+    let err = instruction_error(close(&mut h, &[]).await);
+    assert_eq!(err, custom(DepositError::TimelockRequired));
+
+    let ix = instruction::request_withdraw(&h.program_id, &user.pubkey(), 0, 2 * LAMPORTS_PER_SOL);
+    h.process(&[ix], &[&user]).await.unwrap();
+    h.advance_clock(1).await;
+    let err = instruction_error(close(&mut h, &[]).await);
+    assert_eq!(err, custom(DepositError::FundsLocked));
+
+    h.advance_clock(60).await;
+    let ix = instruction::claim_withdraw(&h.program_id, &user.pubkey(), 0);
+    h.process(&[ix], &[&user]).await.unwrap();
+    close(&mut h, &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn close_charges_dust_below_the_fee() {
+    let mut h = setup().await;
+    let collector = Keypair::new().pubkey();
+    let ix = instruction::deposit(&h.program_id, &h.payer.pubkey(), LAMPORTS_PER_SOL);
+    h.process(&[ix], &[]).await.unwrap();
+    h.deposit(1_000).await.unwrap();
+    h.set_fees(0, 100, 5_000, &collector).await;
+
+    close(&mut h, &[]).await.unwrap();
+    assert_eq!(h.config().await.accrued_fees, 1_000);
+}
@@ -0,0 +1,206 @@
+#![allow(dead_code)]
+
+use borsh::BorshDeserialize;
+use solana_deposit::process_instruction;
+use solana_deposit_interface::{
+    error::DepositError,
+    find_config_address, find_ledger_address, find_program_data_address, find_withdrawal_address,
+    instruction,
+    state::{Config, DepositAccount, PendingWithdrawal},
+};
+use solana_program_test::{
+    processor, BanksClient, BanksClientError, ProgramTest, ProgramTestContext,
+};
+use solana_sdk::{
+    account::{Account, AccountSharedData, WritableAccount},
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    clock::Clock,
+    instruction::{Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+pub struct Harness {
+    pub context: ProgramTestContext,
+    pub banks: BanksClient,
+    pub payer: Keypair,
+    pub program_id: Pubkey,
+    pub user: Keypair,
+}
+
+/// Starts a bank with the program initialized and the fee payer as admin.
+pub async fn setup() -> Harness {
+    let mut h = setup_uninitialized().await;
+    h.initialize().await;
+    h
+}
+
+pub async fn setup_uninitialized() -> Harness {
+    let program_id = Pubkey::new_unique();
+    let user = Keypair::new();
+
+    let mut program_test = ProgramTest::new(
+        "solana_deposit",
+        program_id,
+        processor!(process_instruction),
+    );
+    program_test.add_account(
+        user.pubkey(),
+        Account::new(10 * LAMPORTS_PER_SOL, 0, &solana_sdk::system_program::id()),
+    );
+
+    let context = program_test.start_with_context().await;
+    let mut h = Harness {
+        banks: context.banks_client.clone(),
+        payer: context.payer.insecure_clone(),
+        context,
+        program_id,
+        user,
+    };
+    let payer = h.payer.pubkey();
+    h.set_upgrade_authority(&payer).await;
+    h
+}
+
+impl Harness {
+    pub async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        let blockhash = self.banks.get_latest_blockhash().await.unwrap();
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+        self.banks.process_transaction(transaction).await
+    }
+
+    pub async fn initialize(&mut self) {
+        let ix = instruction::initialize(&self.program_id, &self.payer.pubkey());
+        self.process(&[ix], &[]).await.unwrap();
+    }
+
+    pub async fn config(&mut self) -> Config {
+        let address = find_config_address(&self.program_id).0;
+        let account = self.banks.get_account(address).await.unwrap().unwrap();
+        Config::deserialize(&mut &account.data[..]).unwrap()
+    }
+
+    /// Sets the fees as the admin (the fee payer).
+    pub async fn set_fees(
+        &mut self,
+        deposit_fee_bps: u16,
+        withdraw_fee_bps: u16,
+        min_fee: u64,
+        fee_collector: &Pubkey,
+    ) {
+        let ix = instruction::set_fees(
+            &self.program_id,
+            &self.payer.pubkey(),
+            deposit_fee_bps,
+            withdraw_fee_bps,
+            min_fee,
+            fee_collector,
+        );
+        self.process(&[ix], &[]).await.unwrap();
+    }
+
+    pub async fn deposit(&mut self, amount: u64) -> Result<(), BanksClientError> {
+        let user = self.user.insecure_clone();
+        let ix = instruction::deposit(&self.program_id, &user.pubkey(), amount);
+        self.process(&[ix], &[&user]).await
+    }
+
+    pub async fn withdraw(&mut self, amount: u64) -> Result<(), BanksClientError> {
+        let user = self.user.insecure_clone();
+        let ix = instruction::withdraw(&self.program_id, &user.pubkey(), amount);
+        self.process(&[ix], &[&user]).await
+    }
+
+    pub async fn ledger_balance(&mut self) -> u64 {
+        let ledger = find_ledger_address(&self.program_id, &self.user.pubkey()).0;
+        let account = self.banks.get_account(ledger).await.unwrap().unwrap();
+        assert_eq!(account.owner, self.program_id);
+        DepositAccount::deserialize(&mut &account.data[..])
+            .unwrap()
+            .balance
+    }
+
+    pub async fn ledger(&mut self, user: &Pubkey) -> DepositAccount {
+        let ledger = find_ledger_address(&self.program_id, user).0;
+        let account = self.banks.get_account(ledger).await.unwrap().unwrap();
+        DepositAccount::deserialize(&mut &account.data[..]).unwrap()
+    }
+
+    pub async fn withdrawal(&mut self, user: &Pubkey, id: u64) -> Option<PendingWithdrawal> {
+        let address = find_withdrawal_address(&self.program_id, user, id).0;
+        let account = self.banks.get_account(address).await.unwrap()?;
+        Some(PendingWithdrawal::deserialize(&mut &account.data[..]).unwrap())
+    }
+
+    /// Moves the bank clock forward by `seconds` on a fresh slot, so retried
+    /// transactions get a new blockhash.
+    pub async fn advance_clock(&mut self, seconds: i64) {
+        let slot = self.banks.get_root_slot().await.unwrap();
+        self.context.warp_to_slot(slot + 2).unwrap();
+        let mut clock: Clock = self.banks.get_sysvar().await.unwrap();
+        clock.unix_timestamp += seconds;
+        self.context.set_sysvar(&clock);
+    }
+
+    /// Records `authority` as upgrade authority in the program's ProgramData
+    /// account, as a deploy through the upgradeable loader would.
+    pub async fn set_upgrade_authority(&mut self, authority: &Pubkey) {
+        let mut data = Vec::with_capacity(UpgradeableLoaderState::size_of_programdata_metadata());
+        data.extend_from_slice(&3u32.to_le_bytes());
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(authority.as_ref());
+
+        // Funded out of the payer so the bank's capitalization stays
+        // consistent when tests warp later.
+        let rent = self
+            .banks
+            .get_rent()
+            .await
+            .unwrap()
+            .minimum_balance(data.len());
+        let payer = self.payer.pubkey();
+        let mut payer_account: AccountSharedData =
+            self.banks.get_account(payer).await.unwrap().unwrap().into();
+        payer_account.checked_sub_lamports(rent).unwrap();
+        self.context.set_account(&payer, &payer_account);
+
+        let mut account = AccountSharedData::new(rent, data.len(), &bpf_loader_upgradeable::id());
+        account.set_data_from_slice(&data);
+        let address = find_program_data_address(&self.program_id).0;
+        self.context.set_account(&address, &account);
+    }
+
+    /// Rent-exempt reserve `Initialize` leaves in the vault.
+    pub async fn vault_reserve(&mut self) -> u64 {
+        self.banks.get_rent().await.unwrap().minimum_balance(0)
+    }
+
+    pub async fn lamports(&mut self, address: Pubkey) -> u64 {
+        self.banks.get_balance(address).await.unwrap()
+    }
+}
+
+pub fn instruction_error(result: Result<(), BanksClientError>) -> InstructionError {
+    match result.unwrap_err().unwrap() {
+        TransactionError::InstructionError(0, e) => e,
+        e => panic!("unexpected error: {:?}", e),
+    }
+}
+
+pub fn custom(error: DepositError) -> InstructionError {
+    InstructionError::Custom(error as u32)
+}
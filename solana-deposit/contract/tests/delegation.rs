@@ -0,0 +1,129 @@
+mod common;
+
+use borsh::BorshDeserialize;
+use common::{custom, instruction_error, setup, Harness};
+use solana_deposit_interface::{
+    error::DepositError, find_delegation_address, instruction, state::Delegation,
+};
+use solana_program_test::BanksClientError;
+use solana_sdk::{
+    clock::Clock,
+    instruction::InstructionError,
+    native_token::LAMPORTS_PER_SOL,
+    signature::{Keypair, Signer},
+};
+
+impl Harness {
+    async fn approve(&mut self, delegate: &Keypair, allowance: u64, expiry: i64) {
+        let user = self.user.insecure_clone();
+        let ix = instruction::approve_delegate(
+            &self.program_id,
+            &user.pubkey(),
+            &delegate.pubkey(),
+            allowance,
+            expiry,
+        );
+        self.process(&[ix], &[&user]).await.unwrap();
+    }
+
+    async fn withdraw_as(
+        &mut self,
+        delegate: &Keypair,
+        amount: u64,
+    ) -> Result<(), BanksClientError> {
+        let ix = instruction::withdraw_delegated(
+            &self.program_id,
+            &delegate.pubkey(),
+            &self.user.pubkey(),
+            amount,
+        );
+        self.process(&[ix], &[delegate]).await
+    }
+
+    async fn delegation(&mut self, delegate: &Keypair) -> Option<Delegation> {
+        let address =
+            find_delegation_address(&self.program_id, &self.user.pubkey(), &delegate.pubkey()).0;
+        let account = self.banks.get_account(address).await.unwrap()?;
+        Some(Delegation::deserialize(&mut &account.data[..]).unwrap())
+    }
+}
+
+#[tokio::test]
+async fn delegate_withdraws_to_owner_within_allowance() {
+    let mut h = setup().await;
+    let delegate = Keypair::new();
+    h.deposit(3 * LAMPORTS_PER_SOL).await.unwrap();
+    h.approve(&delegate, 2 * LAMPORTS_PER_SOL, 0).await;
+
+    let before = h.lamports(h.user.pubkey()).await;
+    h.withdraw_as(&delegate, LAMPORTS_PER_SOL).await.unwrap();
+    assert_eq!(h.lamports(h.user.pubkey()).await, before + LAMPORTS_PER_SOL);
+    assert_eq!(h.ledger_balance().await, 2 * LAMPORTS_PER_SOL);
+    assert_eq!(
+        h.delegation(&delegate).await.unwrap().allowance,
+        LAMPORTS_PER_SOL
+    );
+
+    let err = instruction_error(h.withdraw_as(&delegate, 2 * LAMPORTS_PER_SOL).await);
+    assert_eq!(err, custom(DepositError::AllowanceExceeded));
+}
+
+#[tokio::test]
+async fn expired_delegation_is_rejected() {
+    let mut h = setup().await;
+    let delegate = Keypair::new();
+    h.deposit(2 * LAMPORTS_PER_SOL).await.unwrap();
+
+    let clock: Clock = h.banks.get_sysvar().await.unwrap();
+    h.approve(&delegate, LAMPORTS_PER_SOL, clock.unix_timestamp + 60)
+        .await;
+    h.advance_clock(120).await;
+
+    let err = instruction_error(h.withdraw_as(&delegate, LAMPORTS_PER_SOL).await);
+    assert_eq!(err, custom(DepositError::DelegationExpired));
+}
+
+#[tokio::test]
+async fn revoked_delegate_cannot_withdraw() {
+    let mut h = setup().await;
+    let delegate = Keypair::new();
+    h.deposit(2 * LAMPORTS_PER_SOL).await.unwrap();
+    h.approve(&delegate, LAMPORTS_PER_SOL, 0).await;
+
+    let user = h.user.insecure_clone();
+    let ix = instruction::revoke_delegate(&h.program_id, &user.pubkey(), &delegate.pubkey());
+    h.process(&[ix], &[&user]).await.unwrap();
+    assert!(h.delegation(&delegate).await.is_none());
+
+    let err = instruction_error(h.withdraw_as(&delegate, LAMPORTS_PER_SOL).await);
+    assert_eq!(err, custom(DepositError::DelegationMismatch));
+}
+
+#[tokio::test]
+async fn other_signer_cannot_use_a_delegation() {
+    let mut h = setup().await;
+    let delegate = Keypair::new();
+    let intruder = Keypair::new();
+    h.deposit(2 * LAMPORTS_PER_SOL).await.unwrap();
+    h.approve(&delegate, LAMPORTS_PER_SOL, 0).await;
+
+    let mut ix = instruction::withdraw_delegated(
+        &h.program_id,
+        &delegate.pubkey(),
+        &h.user.pubkey(),
+        LAMPORTS_PER_SOL,
+    );
+    ix.accounts[5].pubkey = intruder.pubkey();
+    let err = instruction_error(h.process(&[ix], &[&intruder]).await);
+    assert_eq!(err, custom(DepositError::DelegationMismatch));
+
+    let mut ix = instruction::withdraw_delegated(
+        &h.program_id,
+        &delegate.pubkey(),
+        &h.user.pubkey(),
+        LAMPORTS_PER_SOL,
+    );
+    ix.accounts[5].is_signer = false;
+    let err = instruction_error(h.process(&[ix], &[]).await);
+    assert_eq!(err, InstructionError::MissingRequiredSignature);
+}
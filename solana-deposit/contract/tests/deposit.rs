@@ -0,0 +1,165 @@
+mod common;
+
+use common::{custom, instruction_error, setup};
+use solana_deposit_interface::{
+    error::DepositError, find_ledger_address, find_vault_address, instruction,
+};
+use solana_sdk::{
+    instruction::InstructionError,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+#[tokio::test]
+async fn deposit_moves_lamports_into_vault_and_credits_ledger() {
+    let mut h = setup().await;
+    let vault = find_vault_address(&h.program_id).0;
+    let reserve = h.vault_reserve().await;
+
+    h.deposit(2 * LAMPORTS_PER_SOL).await.unwrap();
+    h.deposit(LAMPORTS_PER_SOL).await.unwrap();
+
+    assert_eq!(h.ledger_balance().await, 3 * LAMPORTS_PER_SOL);
+    assert_eq!(h.lamports(vault).await, reserve + 3 * LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn withdraw_pays_user_from_vault_and_debits_ledger() {
+    let mut h = setup().await;
+    let vault = find_vault_address(&h.program_id).0;
+    let reserve = h.vault_reserve().await;
+
+    h.deposit(3 * LAMPORTS_PER_SOL).await.unwrap();
+    let before = h.lamports(h.user.pubkey()).await;
+
+    h.withdraw(LAMPORTS_PER_SOL).await.unwrap();
+
+    assert_eq!(h.ledger_balance().await, 2 * LAMPORTS_PER_SOL);
+    assert_eq!(h.lamports(vault).await, reserve + 2 * LAMPORTS_PER_SOL);
+    assert_eq!(h.lamports(h.user.pubkey()).await, before + LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn small_first_deposit_is_accepted() {
+    let mut h = setup().await;
+    let vault = find_vault_address(&h.program_id).0;
+    let reserve = h.vault_reserve().await;
+
+    h.deposit(1_000).await.unwrap();
+
+    assert_eq!(h.ledger_balance().await, 1_000);
+    assert_eq!(h.lamports(vault).await, reserve + 1_000);
+}
+
+#[tokio::test]
+async fn full_withdraw_leaves_vault_at_rent_reserve() {
+    let mut h = setup().await;
+    let vault = find_vault_address(&h.program_id).0;
+    let reserve = h.vault_reserve().await;
+
+    h.deposit(LAMPORTS_PER_SOL).await.unwrap();
+    h.withdraw(LAMPORTS_PER_SOL).await.unwrap();
+
+    assert_eq!(h.ledger_balance().await, 0);
+    assert_eq!(h.lamports(vault).await, reserve);
+}
+
+#[tokio::test]
+async fn withdraw_more_than_ledger_balance_fails() {
+    let mut h = setup().await;
+
+    h.deposit(LAMPORTS_PER_SOL).await.unwrap();
+    let err = instruction_error(h.withdraw(LAMPORTS_PER_SOL + 1).await);
+
+    assert_eq!(err, custom(DepositError::LedgerOverdrawn));
+    assert_eq!(h.ledger_balance().await, LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn withdraw_cannot_drain_other_users_deposits() {
+    let mut h = setup().await;
+    h.deposit(2 * LAMPORTS_PER_SOL).await.unwrap();
+
+    let thief = Keypair::new();
+    let ix = instruction::withdraw(&h.program_id, &thief.pubkey(), LAMPORTS_PER_SOL);
+    let err = instruction_error(h.process(&[ix], &[&thief]).await);
+
+    assert_eq!(err, custom(DepositError::LedgerNotFound));
+}
+
+#[tokio::test]
+async fn deposit_without_user_signature_fails() {
+    let mut h = setup().await;
+
+    let mut ix = instruction::deposit(&h.program_id, &h.user.pubkey(), LAMPORTS_PER_SOL);
+    ix.accounts[0].is_signer = false;
+    let err = instruction_error(h.process(&[ix], &[]).await);
+
+    assert_eq!(err, InstructionError::MissingRequiredSignature);
+}
+
+#[tokio::test]
+async fn withdraw_without_user_signature_fails() {
+    let mut h = setup().await;
+    h.deposit(LAMPORTS_PER_SOL).await.unwrap();
+
+    let mut ix = instruction::withdraw(&h.program_id, &h.user.pubkey(), LAMPORTS_PER_SOL);
+    ix.accounts[0].is_signer = false;
+    let err = instruction_error(h.process(&[ix], &[]).await);
+
+    assert_eq!(err, InstructionError::MissingRequiredSignature);
+}
+
+#[tokio::test]
+async fn swapped_vault_and_ledger_accounts_are_rejected() {
+    let mut h = setup().await;
+
+    let mut ix = instruction::deposit(&h.program_id, &h.user.pubkey(), LAMPORTS_PER_SOL);
+    ix.accounts.swap(1, 2);
+    let user = h.user.insecure_clone();
+    let err = instruction_error(h.process(&[ix], &[&user]).await);
+
+    assert_eq!(err, custom(DepositError::VaultMismatch));
+}
+
+#[tokio::test]
+async fn foreign_ledger_account_is_rejected() {
+    let mut h = setup().await;
+
+    let mut ix = instruction::deposit(&h.program_id, &h.user.pubkey(), LAMPORTS_PER_SOL);
+    ix.accounts[2].pubkey = find_ledger_address(&h.program_id, &Pubkey::new_unique()).0;
+    let user = h.user.insecure_clone();
+    let err = instruction_error(h.process(&[ix], &[&user]).await);
+
+    assert_eq!(err, custom(DepositError::LedgerMismatch));
+}
+
+#[tokio::test]
+async fn missing_accounts_are_rejected() {
+    let mut h = setup().await;
+
+    let mut ix = instruction::deposit(&h.program_id, &h.user.pubkey(), LAMPORTS_PER_SOL);
+    ix.accounts.truncate(2);
+    let user = h.user.insecure_clone();
+    let err = instruction_error(h.process(&[ix], &[&user]).await);
+
+    assert_eq!(err, InstructionError::NotEnoughAccountKeys);
+}
+
+#[tokio::test]
+async fn malformed_instruction_data_is_rejected() {
+    let mut h = setup().await;
+    let user = h.user.insecure_clone();
+    let template = instruction::deposit(&h.program_id, &user.pubkey(), LAMPORTS_PER_SOL);
+
+    let mut trailing = template.data.clone();
+    trailing.push(0);
+
+    for data in [vec![], vec![0u8; 4], vec![0xff; 9], trailing] {
+        let mut ix = template.clone();
+        ix.data = data;
+        let err = instruction_error(h.process(&[ix], &[&user]).await);
+        assert_eq!(err, InstructionError::InvalidInstructionData);
+    }
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use common::{custom, instruction_error, setup, Harness};
+use solana_deposit_interface::{
+    error::DepositError, find_escrow_address, find_vault_address, instruction,
+};
+use solana_sdk::{
+    native_token::LAMPORTS_PER_SOL,
+    signature::{Keypair, Signer},
+};
+
+const TIMEOUT: i64 = 600;
+
+/// Locks 1 SOL from the user in escrow 0 for a fresh counterparty.
+async fn setup_escrow() -> (Harness, Keypair) {
+    let mut h = setup().await;
+    let depositor = h.user.insecure_clone();
+    let counterparty = Keypair::new();
+
+    let ix = instruction::escrow_create(
+        &h.program_id,
+        &depositor.pubkey(),
+        0,
+        LAMPORTS_PER_SOL,
+        &counterparty.pubkey(),
+        TIMEOUT,
+    );
+    h.process(&[ix], &[&depositor]).await.unwrap();
+    (h, counterparty)
+}
+
+#[tokio::test]
+async fn released_escrow_is_claimed_by_counterparty() {
+    let (mut h, counterparty) = setup_escrow().await;
+    let depositor = h.user.insecure_clone();
+    let vault = find_vault_address(&h.program_id).0;
+    let reserve = h.vault_reserve().await;
+    assert_eq!(h.lamports(vault).await, reserve + LAMPORTS_PER_SOL);
+
+    let claim = instruction::escrow_claim(
+        &h.program_id,
+        &counterparty.pubkey(),
+        &depositor.pubkey(),
+        0,
+    );
+    let err = instruction_error(
+        h.process(std::slice::from_ref(&claim), &[&counterparty])
+            .await,
+    );
+    assert_eq!(err, custom(DepositError::EscrowNotReleased));
+
+    let ix = instruction::escrow_release(&h.program_id, &depositor.pubkey(), 0);
+    h.process(&[ix], &[&depositor]).await.unwrap();
+    h.process(&[claim], &[&counterparty]).await.unwrap();
+
+    assert_eq!(h.lamports(counterparty.pubkey()).await, LAMPORTS_PER_SOL);
+    assert_eq!(h.lamports(vault).await, reserve);
+    let escrow = find_escrow_address(&h.program_id, &depositor.pubkey(), 0).0;
+    assert!(h.banks.get_account(escrow).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn only_the_named_counterparty_can_claim() {
+    let (mut h, _) = setup_escrow().await;
+    let depositor = h.user.insecure_clone();
+    let ix = instruction::escrow_release(&h.program_id, &depositor.pubkey(), 0);
+    h.process(&[ix], &[&depositor]).await.unwrap();
+
+    let impostor = Keypair::new();
+    let ix = instruction::escrow_claim(&h.program_id, &impostor.pubkey(), &depositor.pubkey(), 0);
+    let err = instruction_error(h.process(&[ix], &[&impostor]).await);
+    assert_eq!(err, custom(DepositError::EscrowMismatch));
+}
+
+#[tokio::test]
+async fn depositor_reclaims_unreleased_escrow_after_timeout() {
+    let (mut h, _) = setup_escrow().await;
+    let depositor = h.user.insecure_clone();
+
+    let refund = instruction::escrow_refund(&h.program_id, &depositor.pubkey(), 0);
+    let err = instruction_error(
+        h.process(std::slice::from_ref(&refund), &[&depositor])
+            .await,
+    );
+    assert_eq!(err, custom(DepositError::EscrowLocked));
+
+    h.advance_clock(TIMEOUT).await;
+    let before = h.lamports(depositor.pubkey()).await;
+    h.process(&[refund], &[&depositor]).await.unwrap();
+
+    assert!(h.lamports(depositor.pubkey()).await > before + LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn released_escrow_cannot_be_refunded() {
+    let (mut h, _) = setup_escrow().await;
+    let depositor = h.user.insecure_clone();
+    let ix = instruction::escrow_release(&h.program_id, &depositor.pubkey(), 0);
+    h.process(&[ix], &[&depositor]).await.unwrap();
+
+    h.advance_clock(TIMEOUT).await;
+    let ix = instruction::escrow_refund(&h.program_id, &depositor.pubkey(), 0);
+    let err = instruction_error(h.process(&[ix], &[&depositor]).await);
+    assert_eq!(err, custom(DepositError::EscrowReleased));
+}
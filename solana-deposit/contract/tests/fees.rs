@@ -0,0 +1,127 @@
+mod common;
+
+use common::{custom, instruction_error, setup};
+use solana_deposit_interface::{
+    error::DepositError, find_vault_address, instruction, state::MAX_FEE_BPS,
+};
+use solana_sdk::{
+    instruction::InstructionError,
+    native_token::LAMPORTS_PER_SOL,
+    signature::{Keypair, Signer},
+};
+
+const MIN_FEE: u64 = 1_000_000;
+
+#[tokio::test]
+async fn deposit_fee_is_withheld_from_the_ledger_credit() {
+    let mut h = setup().await;
+    let vault = find_vault_address(&h.program_id).0;
+    let reserve = h.vault_reserve().await;
+    h.set_fees(100, 0, MIN_FEE, &Keypair::new().pubkey()).await;
+
+    h.deposit(2 * LAMPORTS_PER_SOL).await.unwrap();
+
+    let fee = 2 * LAMPORTS_PER_SOL / 100;
+    assert_eq!(h.ledger_balance().await, 2 * LAMPORTS_PER_SOL - fee);
+    assert_eq!(h.config().await.accrued_fees, fee);
+    assert_eq!(h.lamports(vault).await, reserve + 2 * LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn withdraw_fee_is_withheld_from_the_payout() {
+    let mut h = setup().await;
+    h.deposit(2 * LAMPORTS_PER_SOL).await.unwrap();
+    h.set_fees(0, 50, MIN_FEE, &Keypair::new().pubkey()).await;
+    let before = h.lamports(h.user.pubkey()).await;
+
+    h.withdraw(LAMPORTS_PER_SOL).await.unwrap();
+
+    let fee = LAMPORTS_PER_SOL / 200;
+    assert_eq!(h.ledger_balance().await, LAMPORTS_PER_SOL);
+    assert_eq!(
+        h.lamports(h.user.pubkey()).await,
+        before + LAMPORTS_PER_SOL - fee
+    );
+    assert_eq!(h.config().await.accrued_fees, fee);
+}
+
+#[tokio::test]
+async fn claimed_withdrawal_pays_the_withdraw_fee() {
+    let mut h = setup().await;
+    let user = h.user.insecure_clone();
+    h.deposit(LAMPORTS_PER_SOL).await.unwrap();
+    h.set_fees(0, 100, 0, &Keypair::new().pubkey()).await;
+    let ix = instruction::set_withdraw_delay(&h.program_id, &h.payer.pubkey(), 60);
+    h.process(&[ix], &[]).await.unwrap();
+
+    let ix = instruction::request_withdraw(&h.program_id, &user.pubkey(), 0, LAMPORTS_PER_SOL);
+    h.process(&[ix], &[&user]).await.unwrap();
+    h.advance_clock(60).await;
+    let ix = instruction::claim_withdraw(&h.program_id, &user.pubkey(), 0);
+    h.process(&[ix], &[&user]).await.unwrap();
+
+    assert_eq!(h.config().await.accrued_fees, LAMPORTS_PER_SOL / 100);
+}
+
+#[tokio::test]
+async fn small_amounts_pay_the_minimum_fee() {
+    let mut h = setup().await;
+    h.set_fees(100, 100, MIN_FEE, &Keypair::new().pubkey())
+        .await;
+
+    h.deposit(10 * MIN_FEE).await.unwrap();
+    assert_eq!(h.ledger_balance().await, 9 * MIN_FEE);
+
+    let err = instruction_error(h.deposit(MIN_FEE - 1).await);
+    assert_eq!(err, custom(DepositError::AmountBelowFee));
+}
+
+#[tokio::test]
+async fn fee_collector_collects_accrued_fees() {
+    let mut h = setup().await;
+    let vault = find_vault_address(&h.program_id).0;
+    let reserve = h.vault_reserve().await;
+    let collector = Keypair::new();
+    h.set_fees(100, 0, 0, &collector.pubkey()).await;
+    h.deposit(LAMPORTS_PER_SOL).await.unwrap();
+
+    let user = h.user.insecure_clone();
+    let ix = instruction::collect_fees(&h.program_id, &user.pubkey());
+    let err = instruction_error(h.process(&[ix], &[&user]).await);
+    assert_eq!(err, custom(DepositError::Unauthorized));
+
+    let ix = instruction::collect_fees(&h.program_id, &collector.pubkey());
+    h.process(&[ix], &[&collector]).await.unwrap();
+
+    let fee = LAMPORTS_PER_SOL / 100;
+    assert_eq!(h.lamports(collector.pubkey()).await, fee);
+    assert_eq!(h.lamports(vault).await, reserve + LAMPORTS_PER_SOL - fee);
+    assert_eq!(h.config().await.accrued_fees, 0);
+}
+
+#[tokio::test]
+async fn only_admin_can_set_fees_within_bounds() {
+    let mut h = setup().await;
+    let user = h.user.insecure_clone();
+
+    let ix = instruction::set_fees(&h.program_id, &user.pubkey(), 10, 10, 0, &user.pubkey());
+    let err = instruction_error(h.process(&[ix], &[&user]).await);
+    assert_eq!(err, custom(DepositError::Unauthorized));
+
+    for (deposit_fee_bps, withdraw_fee_bps) in [(MAX_FEE_BPS + 1, 0), (0, MAX_FEE_BPS + 1)] {
+        let ix = instruction::set_fees(
+            &h.program_id,
+            &h.payer.pubkey(),
+            deposit_fee_bps,
+            withdraw_fee_bps,
+            0,
+            &user.pubkey(),
+        );
+        let err = instruction_error(h.process(&[ix], &[]).await);
+        assert_eq!(err, InstructionError::InvalidArgument);
+    }
+
+    h.set_fees(MAX_FEE_BPS, MAX_FEE_BPS, 0, &user.pubkey())
+        .await;
+    assert_eq!(h.config().await.deposit_fee_bps, MAX_FEE_BPS);
+}
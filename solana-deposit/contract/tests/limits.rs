@@ -0,0 +1,119 @@
+mod common;
+
+use common::{custom, instruction_error, setup, Harness};
+use solana_deposit_interface::{error::DepositError, instruction};
+use solana_sdk::{
+    native_token::LAMPORTS_PER_SOL,
+    signature::{Keypair, Signer},
+};
+
+async fn set_limits(h: &mut Harness, max_deposit: u64, max_user_balance: u64, max_tvl: u64) {
+    let ix = instruction::set_limits(
+        &h.program_id,
+        &h.payer.pubkey(),
+        max_deposit,
+        max_user_balance,
+        max_tvl,
+    );
+    h.process(&[ix], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn deposit_over_per_transaction_limit_fails() {
+    let mut h = setup().await;
+    set_limits(&mut h, LAMPORTS_PER_SOL, 0, 0).await;
+
+    let err = instruction_error(h.deposit(LAMPORTS_PER_SOL + 1).await);
+    assert_eq!(err, custom(DepositError::DepositTooLarge));
+
+    h.deposit(LAMPORTS_PER_SOL).await.unwrap();
+    assert_eq!(h.ledger_balance().await, LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn deposit_over_per_user_cap_fails() {
+    let mut h = setup().await;
+    set_limits(&mut h, 0, 2 * LAMPORTS_PER_SOL, 0).await;
+
+    h.deposit(3 * LAMPORTS_PER_SOL / 2).await.unwrap();
+    let err = instruction_error(h.deposit(LAMPORTS_PER_SOL).await);
+    assert_eq!(err, custom(DepositError::UserCapExceeded));
+
+    h.deposit(LAMPORTS_PER_SOL / 2).await.unwrap();
+    assert_eq!(h.ledger_balance().await, 2 * LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn deposits_and_escrows_share_the_tvl_cap() {
+    let mut h = setup().await;
+    set_limits(&mut h, 0, 0, 3 * LAMPORTS_PER_SOL).await;
+    h.deposit(2 * LAMPORTS_PER_SOL).await.unwrap();
+
+    let err = instruction_error(h.deposit(3 * LAMPORTS_PER_SOL / 2).await);
+    assert_eq!(err, custom(DepositError::TvlCapExceeded));
+
+    let user = h.user.insecure_clone();
+    let ix = instruction::escrow_create(
+        &h.program_id,
+        &user.pubkey(),
+        0,
+        2 * LAMPORTS_PER_SOL,
+        &Keypair::new().pubkey(),
+        60,
+    );
+    let err = instruction_error(h.process(&[ix], &[&user]).await);
+    assert_eq!(err, custom(DepositError::TvlCapExceeded));
+}
+
+#[tokio::test]
+async fn tvl_cap_counts_deposits_not_vault_balance() {
+    let mut h = setup().await;
+    let user = h.user.insecure_clone();
+    set_limits(&mut h, 0, 0, 3 * LAMPORTS_PER_SOL).await;
+    h.deposit(LAMPORTS_PER_SOL).await.unwrap();
+    let ix = instruction::distribute_rewards(&h.program_id, &h.payer.pubkey(), LAMPORTS_PER_SOL);
+    h.process(&[ix], &[]).await.unwrap();
+
+    let ix = instruction::escrow_create(
+        &h.program_id,
+        &user.pubkey(),
+        0,
+        2 * LAMPORTS_PER_SOL,
+        &Keypair::new().pubkey(),
+        0,
+    );
+    h.process(&[ix], &[&user]).await.unwrap();
+    assert_eq!(h.config().await.total_locked, 2 * LAMPORTS_PER_SOL);
+    let err = instruction_error(h.deposit(1).await);
+    assert_eq!(err, custom(DepositError::TvlCapExceeded));
+
+    let ix = instruction::escrow_refund(&h.program_id, &user.pubkey(), 0);
+    h.process(&[ix], &[&user]).await.unwrap();
+    assert_eq!(h.config().await.total_locked, 0);
+    h.deposit(2 * LAMPORTS_PER_SOL).await.unwrap();
+    assert_eq!(h.config().await.tvl(), 3 * LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn tvl_cap_counts_deposits_after_fees() {
+    let mut h = setup().await;
+    set_limits(&mut h, 0, 0, LAMPORTS_PER_SOL).await;
+    h.set_fees(1_000, 0, 0, &Keypair::new().pubkey()).await;
+
+    let err = instruction_error(h.deposit(LAMPORTS_PER_SOL * 10 / 9 + 1).await);
+    assert_eq!(err, custom(DepositError::TvlCapExceeded));
+
+    h.deposit(LAMPORTS_PER_SOL * 10 / 9).await.unwrap();
+    assert_eq!(h.config().await.tvl(), LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn only_admin_can_set_limits() {
+    let mut h = setup().await;
+    let user = h.user.insecure_clone();
+
+    let ix = instruction::set_limits(&h.program_id, &user.pubkey(), 1, 1, 1);
+    let err = instruction_error(h.process(&[ix], &[&user]).await);
+    assert_eq!(err, custom(DepositError::Unauthorized));
+    assert_eq!(h.config().await.max_tvl, 0);
+}
@@ -0,0 +1,143 @@
+mod common;
+
+use common::{custom, instruction_error, setup, Harness};
+use solana_deposit_interface::{
+    error::DepositError, find_proposal_address, find_vault_address, instruction,
+};
+use solana_sdk::{
+    instruction::InstructionError,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const EXPIRY: i64 = 3600;
+
+/// Deposits 3 SOL for the user and puts their ledger under a 2-of-3 multisig
+/// of the user and two extra signers.
+async fn setup_multisig() -> (Harness, Keypair, Keypair) {
+    let mut h = setup().await;
+    h.deposit(3 * LAMPORTS_PER_SOL).await.unwrap();
+
+    let user = h.user.insecure_clone();
+    let (alice, bob) = (Keypair::new(), Keypair::new());
+    let signers = [user.pubkey(), alice.pubkey(), bob.pubkey()];
+    let ix = instruction::set_multisig(&h.program_id, &user.pubkey(), &signers, 2);
+    h.process(&[ix], &[&user]).await.unwrap();
+    (h, alice, bob)
+}
+
+async fn propose(h: &mut Harness, id: u64, recipient: &Pubkey, expires_in: i64) {
+    let user = h.user.insecure_clone();
+    let ix = instruction::propose_withdraw(
+        &h.program_id,
+        &user.pubkey(),
+        &user.pubkey(),
+        id,
+        LAMPORTS_PER_SOL,
+        recipient,
+        expires_in,
+    );
+    h.process(&[ix], &[&user]).await.unwrap();
+}
+
+#[tokio::test]
+async fn direct_withdrawals_are_blocked_once_multisig_is_set() {
+    let (mut h, _, _) = setup_multisig().await;
+    let user = h.user.insecure_clone();
+
+    let err = instruction_error(h.withdraw(LAMPORTS_PER_SOL).await);
+    assert_eq!(err, custom(DepositError::MultisigRequired));
+
+    let ix = instruction::request_withdraw(&h.program_id, &user.pubkey(), 0, LAMPORTS_PER_SOL);
+    let err = instruction_error(h.process(&[ix], &[&user]).await);
+    assert_eq!(err, custom(DepositError::MultisigRequired));
+    assert!(h.ledger(&user.pubkey()).await.multisig);
+}
+
+#[tokio::test]
+async fn proposal_executes_once_threshold_is_met() {
+    let (mut h, alice, _) = setup_multisig().await;
+    let owner = h.user.pubkey();
+    let recipient = Keypair::new().pubkey();
+    let vault = find_vault_address(&h.program_id).0;
+    let reserve = h.vault_reserve().await;
+    propose(&mut h, 0, &recipient, EXPIRY).await;
+
+    let execute = instruction::execute_withdraw(&h.program_id, &owner, 0, &owner, &recipient);
+    let err = instruction_error(h.process(std::slice::from_ref(&execute), &[]).await);
+    assert_eq!(err, custom(DepositError::ThresholdNotMet));
+
+    let ix = instruction::approve_withdraw(&h.program_id, &alice.pubkey(), &owner, 0);
+    h.process(&[ix], &[&alice]).await.unwrap();
+    h.process(&[execute], &[]).await.unwrap();
+
+    assert_eq!(h.lamports(recipient).await, LAMPORTS_PER_SOL);
+    assert_eq!(h.lamports(vault).await, reserve + 2 * LAMPORTS_PER_SOL);
+    assert_eq!(h.ledger(&owner).await.balance, 2 * LAMPORTS_PER_SOL);
+    let proposal = find_proposal_address(&h.program_id, &owner, 0).0;
+    assert!(h.banks.get_account(proposal).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn only_signers_approve_and_only_once() {
+    let (mut h, alice, _) = setup_multisig().await;
+    let owner = h.user.pubkey();
+    propose(&mut h, 0, &owner, EXPIRY).await;
+
+    let outsider = Keypair::new();
+    let ix = instruction::approve_withdraw(&h.program_id, &outsider.pubkey(), &owner, 0);
+    let err = instruction_error(h.process(&[ix], &[&outsider]).await);
+    assert_eq!(err, custom(DepositError::NotMultisigSigner));
+
+    let ix = instruction::approve_withdraw(&h.program_id, &alice.pubkey(), &owner, 0);
+    h.process(std::slice::from_ref(&ix), &[&alice])
+        .await
+        .unwrap();
+    h.advance_clock(1).await;
+    let err = instruction_error(h.process(&[ix], &[&alice]).await);
+    assert_eq!(err, custom(DepositError::AlreadyApproved));
+}
+
+#[tokio::test]
+async fn expired_proposal_cannot_execute_and_anyone_may_close_it() {
+    let (mut h, alice, bob) = setup_multisig().await;
+    let owner = h.user.pubkey();
+    propose(&mut h, 0, &owner, 60).await;
+    let ix = instruction::approve_withdraw(&h.program_id, &alice.pubkey(), &owner, 0);
+    h.process(&[ix], &[&alice]).await.unwrap();
+
+    let ix = instruction::cancel_proposal(&h.program_id, &bob.pubkey(), &owner, 0, &owner);
+    let err = instruction_error(h.process(&[ix], &[&bob]).await);
+    assert_eq!(err, custom(DepositError::Unauthorized));
+
+    h.advance_clock(61).await;
+    let ix = instruction::execute_withdraw(&h.program_id, &owner, 0, &owner, &owner);
+    let err = instruction_error(h.process(&[ix], &[]).await);
+    assert_eq!(err, custom(DepositError::ProposalExpired));
+
+    let ix = instruction::cancel_proposal(&h.program_id, &bob.pubkey(), &owner, 0, &owner);
+    h.process(&[ix], &[&bob]).await.unwrap();
+    assert_eq!(h.ledger(&owner).await.balance, 3 * LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn invalid_signer_sets_are_rejected() {
+    let mut h = setup().await;
+    h.deposit(LAMPORTS_PER_SOL).await.unwrap();
+    let user = h.user.insecure_clone();
+    let other = Pubkey::new_unique();
+
+    for (signers, threshold) in [
+        (vec![user.pubkey(), other], 3),
+        (vec![user.pubkey(), other], 2),
+        (vec![user.pubkey()], 1),
+        (vec![user.pubkey(), other], 0),
+        (vec![other, other], 1),
+        (vec![], 1),
+    ] {
+        let ix = instruction::set_multisig(&h.program_id, &user.pubkey(), &signers, threshold);
+        let err = instruction_error(h.process(&[ix], &[&user]).await);
+        assert_eq!(err, InstructionError::InvalidArgument);
+    }
+}
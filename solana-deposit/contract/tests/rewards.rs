@@ -0,0 +1,147 @@
+mod common;
+
+use common::{custom, instruction_error, setup, Harness};
+use solana_deposit_interface::{error::DepositError, find_vault_address, instruction};
+use solana_sdk::{
+    native_token::LAMPORTS_PER_SOL,
+    signature::{Keypair, Signer},
+};
+
+async fn distribute(h: &mut Harness, amount: u64) {
+    let ix = instruction::distribute_rewards(&h.program_id, &h.payer.pubkey(), amount);
+    h.process(&[ix], &[]).await.unwrap();
+}
+
+async fn pending_rewards(h: &mut Harness) -> u64 {
+    let index = h.config().await.reward_index;
+    h.ledger(&h.user.pubkey()).await.pending_rewards(index)
+}
+
+#[tokio::test]
+async fn rewards_are_shared_by_balance() {
+    let mut h = setup().await;
+    let payer = h.payer.pubkey();
+    h.deposit(LAMPORTS_PER_SOL).await.unwrap();
+    let ix = instruction::deposit(&h.program_id, &payer, 3 * LAMPORTS_PER_SOL);
+    h.process(&[ix], &[]).await.unwrap();
+    assert_eq!(h.config().await.total_deposits, 4 * LAMPORTS_PER_SOL);
+
+    distribute(&mut h, 400_000_000).await;
+    assert_eq!(pending_rewards(&mut h).await, 100_000_000);
+    let index = h.config().await.reward_index;
+    assert_eq!(h.ledger(&payer).await.pending_rewards(index), 300_000_000);
+}
+
+#[tokio::test]
+async fn late_depositor_earns_only_later_rewards() {
+    let mut h = setup().await;
+    let payer = h.payer.pubkey();
+    let ix = instruction::deposit(&h.program_id, &payer, LAMPORTS_PER_SOL);
+    h.process(&[ix], &[]).await.unwrap();
+    distribute(&mut h, 100_000_000).await;
+
+    h.deposit(LAMPORTS_PER_SOL).await.unwrap();
+    assert_eq!(pending_rewards(&mut h).await, 0);
+
+    distribute(&mut h, 200_000_000).await;
+    assert_eq!(pending_rewards(&mut h).await, 100_000_000);
+}
+
+#[tokio::test]
+async fn claim_pays_settled_and_new_rewards() {
+    let mut h = setup().await;
+    let user = h.user.insecure_clone();
+    h.deposit(2 * LAMPORTS_PER_SOL).await.unwrap();
+    distribute(&mut h, 100_000_000).await;
+
+    // Withdrawing settles what the old balance earned.
+    h.withdraw(LAMPORTS_PER_SOL).await.unwrap();
+    assert_eq!(h.ledger(&user.pubkey()).await.rewards, 100_000_000);
+    distribute(&mut h, 50_000_000).await;
+
+    let before = h.lamports(user.pubkey()).await;
+    let ix = instruction::claim_rewards(&h.program_id, &user.pubkey());
+    h.process(&[ix], &[&user]).await.unwrap();
+    assert_eq!(h.lamports(user.pubkey()).await, before + 150_000_000);
+    assert_eq!(pending_rewards(&mut h).await, 0);
+}
+
+#[tokio::test]
+async fn only_admin_distributes_to_existing_deposits() {
+    let mut h = setup().await;
+    let user = h.user.insecure_clone();
+
+    let ix = instruction::distribute_rewards(&h.program_id, &h.payer.pubkey(), LAMPORTS_PER_SOL);
+    let err = instruction_error(h.process(&[ix], &[]).await);
+    assert_eq!(err, custom(DepositError::NoDeposits));
+
+    h.deposit(LAMPORTS_PER_SOL).await.unwrap();
+    let ix = instruction::distribute_rewards(&h.program_id, &user.pubkey(), LAMPORTS_PER_SOL);
+    let err = instruction_error(h.process(&[ix], &[&user]).await);
+    assert_eq!(err, custom(DepositError::Unauthorized));
+}
+
+#[tokio::test]
+async fn multisig_ledger_cannot_claim_alone() {
+    let mut h = setup().await;
+    let user = h.user.insecure_clone();
+    h.deposit(LAMPORTS_PER_SOL).await.unwrap();
+    let signers = [user.pubkey(), Keypair::new().pubkey()];
+    let ix = instruction::set_multisig(&h.program_id, &user.pubkey(), &signers, 1);
+    h.process(&[ix], &[&user]).await.unwrap();
+
+    let ix = instruction::claim_rewards(&h.program_id, &user.pubkey());
+    let err = instruction_error(h.process(&[ix], &[&user]).await);
+    assert_eq!(err, custom(DepositError::MultisigRequired));
+
+    let ix = instruction::distribute_rewards(&h.program_id, &h.payer.pubkey(), 100_000_000);
+    let err = instruction_error(h.process(&[ix], &[]).await);
+    assert_eq!(err, custom(DepositError::NoDeposits));
+}
+
+#[tokio::test]
+async fn every_reward_is_claimable_with_a_multisig_ledger() {
+    let mut h = setup().await;
+    let user = h.user.insecure_clone();
+    let payer = h.payer.pubkey();
+    let vault = find_vault_address(&h.program_id).0;
+    let reserve = h.vault_reserve().await;
+    h.deposit(LAMPORTS_PER_SOL).await.unwrap();
+    let ix = instruction::deposit(&h.program_id, &payer, LAMPORTS_PER_SOL);
+    h.process(&[ix], &[]).await.unwrap();
+    distribute(&mut h, 200_000_000).await;
+
+    let signers = [user.pubkey(), Keypair::new().pubkey()];
+    let ix = instruction::set_multisig(&h.program_id, &user.pubkey(), &signers, 1);
+    h.process(&[ix], &[&user]).await.unwrap();
+    let ledger = h.ledger(&user.pubkey()).await;
+    assert_eq!((ledger.balance, ledger.rewards), (1_100_000_000, 0));
+    assert_eq!(h.config().await.total_deposits, LAMPORTS_PER_SOL);
+
+    distribute(&mut h, 300_000_000).await;
+    assert_eq!(pending_rewards(&mut h).await, 0);
+    let ix = instruction::claim_rewards(&h.program_id, &payer);
+    h.process(&[ix], &[]).await.unwrap();
+    let ix = instruction::withdraw(&h.program_id, &payer, LAMPORTS_PER_SOL);
+    h.process(&[ix], &[]).await.unwrap();
+
+    let ix = instruction::propose_withdraw(
+        &h.program_id,
+        &user.pubkey(),
+        &user.pubkey(),
+        0,
+        1_100_000_000,
+        &user.pubkey(),
+        60,
+    );
+    h.process(&[ix], &[&user]).await.unwrap();
+    let ix = instruction::execute_withdraw(
+        &h.program_id,
+        &user.pubkey(),
+        0,
+        &user.pubkey(),
+        &user.pubkey(),
+    );
+    h.process(&[ix], &[]).await.unwrap();
+    assert_eq!(h.lamports(vault).await, reserve);
+}
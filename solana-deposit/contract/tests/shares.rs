@@ -0,0 +1,172 @@
+mod common;
+
+use common::{setup, Harness};
+use solana_deposit_interface::{
+    find_share_mint_address, find_vault_address, get_share_account_address, instruction,
+};
+use solana_program_test::BanksClientError;
+use solana_sdk::{
+    native_token::LAMPORTS_PER_SOL,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use spl_token::state::{Account as TokenAccount, Mint};
+
+async fn setup_shares() -> Harness {
+    let mut h = setup().await;
+    enable_shares(&mut h).await;
+    h
+}
+
+/// Creates the share mint and the share token accounts of the user and the
+/// fee payer.
+async fn enable_shares(h: &mut Harness) {
+    let payer = h.payer.pubkey();
+    let share_mint = find_share_mint_address(&h.program_id).0;
+    let instructions = [
+        instruction::create_share_mint(&h.program_id, &payer),
+        create_associated_token_account_idempotent(&payer, &payer, &share_mint, &spl_token::id()),
+        create_associated_token_account_idempotent(
+            &payer,
+            &h.user.pubkey(),
+            &share_mint,
+            &spl_token::id(),
+        ),
+    ];
+    h.process(&instructions, &[]).await.unwrap();
+}
+
+async fn shares(h: &mut Harness, owner: &Pubkey) -> u64 {
+    let address = get_share_account_address(&h.program_id, owner);
+    let account = h.banks.get_account(address).await.unwrap().unwrap();
+    TokenAccount::unpack(&account.data).unwrap().amount
+}
+
+async fn supply(h: &mut Harness) -> u64 {
+    let address = find_share_mint_address(&h.program_id).0;
+    let account = h.banks.get_account(address).await.unwrap().unwrap();
+    Mint::unpack(&account.data).unwrap().supply
+}
+
+async fn redeem(h: &mut Harness, holder: &Keypair, shares: u64) -> Result<(), BanksClientError> {
+    let ix = instruction::redeem_shares(&h.program_id, &holder.pubkey(), shares);
+    h.process(&[ix], &[holder]).await
+}
+
+#[tokio::test]
+async fn deposit_mints_shares_instead_of_crediting_the_ledger() {
+    let mut h = setup_shares().await;
+    let user = h.user.insecure_clone();
+
+    h.deposit(2 * LAMPORTS_PER_SOL).await.unwrap();
+    assert_eq!(shares(&mut h, &user.pubkey()).await, 2 * LAMPORTS_PER_SOL);
+    assert_eq!(h.ledger_balance().await, 0);
+    assert_eq!(h.config().await.share_pool, 2 * LAMPORTS_PER_SOL);
+    assert_eq!(h.config().await.tvl(), 2 * LAMPORTS_PER_SOL);
+    assert!(h.withdraw(LAMPORTS_PER_SOL).await.is_err());
+
+    let before = h.lamports(user.pubkey()).await;
+    redeem(&mut h, &user, LAMPORTS_PER_SOL / 2).await.unwrap();
+    assert_eq!(
+        h.lamports(user.pubkey()).await,
+        before + LAMPORTS_PER_SOL / 2
+    );
+    assert_eq!(
+        shares(&mut h, &user.pubkey()).await,
+        3 * LAMPORTS_PER_SOL / 2
+    );
+    assert_eq!(supply(&mut h).await, 3 * LAMPORTS_PER_SOL / 2);
+    assert_eq!(h.config().await.share_pool, 3 * LAMPORTS_PER_SOL / 2);
+}
+
+#[tokio::test]
+async fn rewards_raise_the_exchange_rate() {
+    let mut h = setup().await;
+    let user = h.user.insecure_clone();
+    let payer = h.payer.pubkey();
+    let vault = find_vault_address(&h.program_id).0;
+    let reserve = h.vault_reserve().await;
+    let ix = instruction::deposit(&h.program_id, &payer, 2 * LAMPORTS_PER_SOL);
+    h.process(&[ix], &[]).await.unwrap();
+    enable_shares(&mut h).await;
+    h.deposit(2 * LAMPORTS_PER_SOL).await.unwrap();
+
+    let ix = instruction::distribute_rewards(&h.program_id, &payer, LAMPORTS_PER_SOL);
+    h.process(&[ix], &[]).await.unwrap();
+    let config = h.config().await;
+    assert_eq!(config.share_pool, 5 * LAMPORTS_PER_SOL / 2);
+    assert_eq!(
+        config.share_value(LAMPORTS_PER_SOL, supply(&mut h).await),
+        5 * LAMPORTS_PER_SOL / 4
+    );
+
+    let before = h.lamports(user.pubkey()).await;
+    redeem(&mut h, &user, 2 * LAMPORTS_PER_SOL).await.unwrap();
+    assert_eq!(
+        h.lamports(user.pubkey()).await,
+        before + 5 * LAMPORTS_PER_SOL / 2
+    );
+    let ix = instruction::claim_rewards(&h.program_id, &payer);
+    h.process(&[ix], &[]).await.unwrap();
+    let ix = instruction::withdraw(&h.program_id, &payer, 2 * LAMPORTS_PER_SOL);
+    h.process(&[ix], &[]).await.unwrap();
+    assert_eq!(h.lamports(vault).await, reserve);
+}
+
+#[tokio::test]
+async fn transferred_receipts_are_redeemed_by_their_holder() {
+    let mut h = setup_shares().await;
+    let user = h.user.insecure_clone();
+    let holder = h.payer.insecure_clone();
+    let vault = find_vault_address(&h.program_id).0;
+    h.deposit(2 * LAMPORTS_PER_SOL).await.unwrap();
+
+    let ix = spl_token::instruction::transfer(
+        &spl_token::id(),
+        &get_share_account_address(&h.program_id, &user.pubkey()),
+        &get_share_account_address(&h.program_id, &holder.pubkey()),
+        &user.pubkey(),
+        &[],
+        2 * LAMPORTS_PER_SOL,
+    )
+    .unwrap();
+    h.process(&[ix], &[&user]).await.unwrap();
+    assert!(redeem(&mut h, &user, LAMPORTS_PER_SOL).await.is_err());
+
+    let before = h.lamports(vault).await;
+    redeem(&mut h, &holder, 2 * LAMPORTS_PER_SOL).await.unwrap();
+    assert_eq!(h.lamports(vault).await, before - 2 * LAMPORTS_PER_SOL);
+    assert_eq!(shares(&mut h, &holder.pubkey()).await, 0);
+    assert_eq!(h.config().await.share_pool, 0);
+}
+
+#[tokio::test]
+async fn redeem_charges_the_withdrawal_fee() {
+    let mut h = setup_shares().await;
+    let user = h.user.insecure_clone();
+    h.set_fees(0, 100, 0, &Keypair::new().pubkey()).await;
+    h.deposit(2 * LAMPORTS_PER_SOL).await.unwrap();
+
+    let before = h.lamports(user.pubkey()).await;
+    redeem(&mut h, &user, LAMPORTS_PER_SOL).await.unwrap();
+    assert_eq!(h.lamports(user.pubkey()).await, before + 990_000_000);
+    assert_eq!(h.config().await.accrued_fees, 10_000_000);
+    assert_eq!(h.config().await.share_pool, LAMPORTS_PER_SOL);
+}
+
+#[tokio::test]
+async fn multisig_ledgers_are_credited_instead_of_minted() {
+    let mut h = setup_shares().await;
+    let user = h.user.insecure_clone();
+    h.deposit(LAMPORTS_PER_SOL).await.unwrap();
+    let signers = [user.pubkey(), Keypair::new().pubkey()];
+    let ix = instruction::set_multisig(&h.program_id, &user.pubkey(), &signers, 1);
+    h.process(&[ix], &[&user]).await.unwrap();
+
+    h.deposit(2 * LAMPORTS_PER_SOL).await.unwrap();
+    assert_eq!(h.ledger_balance().await, 2 * LAMPORTS_PER_SOL);
+    assert_eq!(shares(&mut h, &user.pubkey()).await, LAMPORTS_PER_SOL);
+    assert_eq!(h.config().await.share_pool, LAMPORTS_PER_SOL);
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use common::{custom, instruction_error, setup, Harness};
+use solana_deposit_interface::{error::DepositError, instruction};
+use solana_sdk::{
+    native_token::LAMPORTS_PER_SOL,
+    signature::{Keypair, Signer},
+};
+
+const DELAY: i64 = 3_600;
+
+async fn setup_with_delay() -> Harness {
+    let mut h = setup().await;
+    let ix = instruction::set_withdraw_delay(&h.program_id, &h.payer.pubkey(), DELAY);
+    h.process(&[ix], &[]).await.unwrap();
+    h.deposit(3 * LAMPORTS_PER_SOL).await.unwrap();
+    h
+}
+
+#[tokio::test]
+async fn direct_withdraw_is_disabled_while_delay_is_set() {
+    let mut h = setup_with_delay().await;
+
+    let err = instruction_error(h.withdraw(LAMPORTS_PER_SOL).await);
+    assert_eq!(err, custom(DepositError::TimelockRequired));
+}
+
+#[tokio::test]
+async fn requested_withdrawal_is_claimable_after_delay() {
+    let mut h = setup_with_delay().await;
+    let user = h.user.insecure_clone();
+
+    let ix = instruction::request_withdraw(&h.program_id, &user.pubkey(), 0, LAMPORTS_PER_SOL);
+    h.process(&[ix], &[&user]).await.unwrap();
+
+    let ledger = h.ledger(&user.pubkey()).await;
+    assert_eq!(ledger.balance, 2 * LAMPORTS_PER_SOL);
+    assert_eq!(ledger.next_withdrawal_id, 1);
+    assert_eq!(ledger.pending_withdrawals, 1);
+    let pending = h.withdrawal(&user.pubkey(), 0).await.unwrap();
+    assert_eq!(pending.amount, LAMPORTS_PER_SOL);
+
+    let ix = instruction::claim_withdraw(&h.program_id, &user.pubkey(), 0);
+    let err = instruction_error(h.process(std::slice::from_ref(&ix), &[&user]).await);
+    assert_eq!(err, custom(DepositError::WithdrawalLocked));
+
+    h.advance_clock(DELAY).await;
+    let before = h.lamports(user.pubkey()).await;
+    h.process(&[ix], &[&user]).await.unwrap();
+
+    assert!(h.lamports(user.pubkey()).await > before + LAMPORTS_PER_SOL);
+    assert!(h.withdrawal(&user.pubkey(), 0).await.is_none());
+    assert_eq!(h.ledger(&user.pubkey()).await.pending_withdrawals, 0);
+}
+
+#[tokio::test]
+async fn cancelled_withdrawal_returns_to_ledger() {
+    let mut h = setup_with_delay().await;
+    let user = h.user.insecure_clone();
+
+    let ix = instruction::request_withdraw(&h.program_id, &user.pubkey(), 0, LAMPORTS_PER_SOL);
+    h.process(&[ix], &[&user]).await.unwrap();
+    let ix = instruction::cancel_withdraw(&h.program_id, &user.pubkey(), 0);
+    h.process(&[ix], &[&user]).await.unwrap();
+
+    let ledger = h.ledger(&user.pubkey()).await;
+    assert_eq!(ledger.balance, 3 * LAMPORTS_PER_SOL);
+    assert_eq!(ledger.pending_withdrawals, 0);
+    assert!(h.withdrawal(&user.pubkey(), 0).await.is_none());
+}
+
+#[tokio::test]
+async fn withdrawal_cannot_be_claimed_by_another_user() {
+    let mut h = setup_with_delay().await;
+    let user = h.user.insecure_clone();
+
+    let ix = instruction::request_withdraw(&h.program_id, &user.pubkey(), 0, LAMPORTS_PER_SOL);
+    h.process(&[ix], &[&user]).await.unwrap();
+    h.advance_clock(DELAY).await;
+
+    let thief = Keypair::new();
+    let mut ix = instruction::claim_withdraw(&h.program_id, &thief.pubkey(), 0);
+    ix.accounts[3] =
+        instruction::claim_withdraw(&h.program_id, &user.pubkey(), 0).accounts[3].clone();
+    let err = instruction_error(h.process(&[ix], &[&thief]).await);
+    assert_eq!(err, custom(DepositError::WithdrawalMismatch));
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use borsh::BorshDeserialize;
+use common::{custom, instruction_error, setup, Harness};
+use solana_deposit_interface::{
+    error::DepositError, find_token_ledger_address, get_token_vault_address, instruction,
+    state::TokenDepositAccount,
+};
+use solana_program_test::BanksClientError;
+use solana_sdk::{
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account,
+};
+use spl_token::state::{Account as TokenAccount, Mint};
+
+const DECIMALS: u8 = 6;
+const MINTED: u64 = 1_000_000_000;
+
+/// Creates a mint and funds the harness user's associated token account.
+async fn setup_mint(h: &mut Harness) -> Pubkey {
+    let mint = Keypair::new();
+    let payer = h.payer.pubkey();
+    let user = h.user.pubkey();
+    let rent = h.banks.get_rent().await.unwrap();
+    let user_ata = get_associated_token_address(&user, &mint.pubkey());
+
+    let instructions = [
+        system_instruction::create_account(
+            &payer,
+            &mint.pubkey(),
+            rent.minimum_balance(Mint::LEN),
+            Mint::LEN as u64,
+            &spl_token::id(),
+        ),
+        spl_token::instruction::initialize_mint2(
+            &spl_token::id(),
+            &mint.pubkey(),
+            &payer,
+            None,
+            DECIMALS,
+        )
+        .unwrap(),
+        create_associated_token_account(&payer, &user, &mint.pubkey(), &spl_token::id()),
+        spl_token::instruction::mint_to(
+            &spl_token::id(),
+            &mint.pubkey(),
+            &user_ata,
+            &payer,
+            &[],
+            MINTED,
+        )
+        .unwrap(),
+    ];
+    h.process(&instructions, &[&mint]).await.unwrap();
+    mint.pubkey()
+}
+
+async fn token_balance(h: &mut Harness, address: Pubkey) -> u64 {
+    let account = h.banks.get_account(address).await.unwrap().unwrap();
+    TokenAccount::unpack(&account.data).unwrap().amount
+}
+
+async fn token_ledger(h: &mut Harness, mint: &Pubkey) -> TokenDepositAccount {
+    let address = find_token_ledger_address(&h.program_id, &h.user.pubkey(), mint).0;
+    let account = h.banks.get_account(address).await.unwrap().unwrap();
+    TokenDepositAccount::deserialize(&mut &account.data[..]).unwrap()
+}
+
+async fn deposit_token(
+    h: &mut Harness,
+    mint: &Pubkey,
+    amount: u64,
+) -> Result<(), BanksClientError> {
+    let user = h.user.insecure_clone();
+    let user_ata = get_associated_token_address(&user.pubkey(), mint);
+    let ix = instruction::deposit_token(&h.program_id, &user.pubkey(), &user_ata, mint, amount);
+    h.process(&[ix], &[&user]).await
+}
+
+async fn withdraw_token(
+    h: &mut Harness,
+    mint: &Pubkey,
+    amount: u64,
+) -> Result<(), BanksClientError> {
+    let user = h.user.insecure_clone();
+    let user_ata = get_associated_token_address(&user.pubkey(), mint);
+    let ix = instruction::withdraw_token(&h.program_id, &user.pubkey(), &user_ata, mint, amount);
+    h.process(&[ix], &[&user]).await
+}
+
+#[tokio::test]
+async fn token_deposit_and_withdraw_round_trip() {
+    let mut h = setup().await;
+    let mint = setup_mint(&mut h).await;
+    let user_ata = get_associated_token_address(&h.user.pubkey(), &mint);
+    let vault_ata = get_token_vault_address(&h.program_id, &mint);
+
+    deposit_token(&mut h, &mint, 300).await.unwrap();
+    deposit_token(&mut h, &mint, 200).await.unwrap();
+
+    let ledger = token_ledger(&mut h, &mint).await;
+    assert_eq!(ledger.balance, 500);
+    assert_eq!(ledger.mint, mint);
+    assert_eq!(token_balance(&mut h, vault_ata).await, 500);
+
+    withdraw_token(&mut h, &mint, 120).await.unwrap();
+
+    assert_eq!(token_ledger(&mut h, &mint).await.balance, 380);
+    assert_eq!(token_balance(&mut h, vault_ata).await, 380);
+    assert_eq!(token_balance(&mut h, user_ata).await, MINTED - 380);
+}
+
+#[tokio::test]
+async fn token_withdraw_is_limited_to_ledger_balance() {
+    let mut h = setup().await;
+    let mint = setup_mint(&mut h).await;
+
+    deposit_token(&mut h, &mint, 100).await.unwrap();
+    let err = instruction_error(withdraw_token(&mut h, &mint, 101).await);
+
+    assert_eq!(err, custom(DepositError::LedgerOverdrawn));
+}
+
+#[tokio::test]
+async fn token_deposit_into_foreign_vault_is_rejected() {
+    let mut h = setup().await;
+    let mint = setup_mint(&mut h).await;
+    let user = h.user.insecure_clone();
+    let user_ata = get_associated_token_address(&user.pubkey(), &mint);
+
+    let mut ix = instruction::deposit_token(&h.program_id, &user.pubkey(), &user_ata, &mint, 10);
+    ix.accounts[2].pubkey = user_ata;
+    let err = instruction_error(h.process(&[ix], &[&user]).await);
+
+    assert_eq!(err, custom(DepositError::TokenVaultMismatch));
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use common::{custom, instruction_error, setup, Harness};
+use solana_deposit_interface::{error::DepositError, find_vesting_address, instruction};
+use solana_sdk::{
+    clock::Clock,
+    instruction::InstructionError,
+    native_token::LAMPORTS_PER_SOL,
+    signature::{Keypair, Signer},
+};
+
+const CLIFF: i64 = 100;
+const DURATION: i64 = 1000;
+
+/// Grants 1 SOL from the user to a fresh beneficiary, vesting linearly over
+/// `DURATION` seconds after a `CLIFF`.
+async fn setup_grant() -> (Harness, Keypair) {
+    let mut h = setup().await;
+    let funder = h.user.insecure_clone();
+    let beneficiary = Keypair::new();
+    let now = h.banks.get_sysvar::<Clock>().await.unwrap().unix_timestamp;
+
+    let ix = instruction::deposit_vested(
+        &h.program_id,
+        &funder.pubkey(),
+        &beneficiary.pubkey(),
+        LAMPORTS_PER_SOL,
+        now,
+        now + CLIFF,
+        now + DURATION,
+    );
+    h.process(&[ix], &[&funder]).await.unwrap();
+    (h, beneficiary)
+}
+
+async fn withdraw_vested(
+    h: &mut Harness,
+    beneficiary: &Keypair,
+    amount: u64,
+) -> Result<(), solana_program_test::BanksClientError> {
+    let ix = instruction::withdraw_vested(
+        &h.program_id,
+        &beneficiary.pubkey(),
+        &h.user.pubkey(),
+        amount,
+    );
+    h.process(&[ix], &[beneficiary]).await
+}
+
+#[tokio::test]
+async fn vested_portion_unlocks_after_cliff_and_in_full_at_end() {
+    let (mut h, beneficiary) = setup_grant().await;
+    let tenth = LAMPORTS_PER_SOL / 10;
+
+    let err = instruction_error(withdraw_vested(&mut h, &beneficiary, 1).await);
+    assert_eq!(err, custom(DepositError::VestingLocked));
+
+    h.advance_clock(CLIFF).await;
+    withdraw_vested(&mut h, &beneficiary, tenth).await.unwrap();
+    assert_eq!(h.lamports(beneficiary.pubkey()).await, tenth);
+    let err = instruction_error(withdraw_vested(&mut h, &beneficiary, 8 * tenth).await);
+    assert_eq!(err, custom(DepositError::VestingLocked));
+
+    h.advance_clock(DURATION).await;
+    let funder_before = h.lamports(h.user.pubkey()).await;
+    withdraw_vested(&mut h, &beneficiary, 9 * tenth)
+        .await
+        .unwrap();
+
+    assert_eq!(h.lamports(beneficiary.pubkey()).await, LAMPORTS_PER_SOL);
+    let schedule = find_vesting_address(&h.program_id, &h.user.pubkey(), &beneficiary.pubkey()).0;
+    assert!(h.banks.get_account(schedule).await.unwrap().is_none());
+    assert!(h.lamports(h.user.pubkey()).await > funder_before);
+}
+
+#[tokio::test]
+async fn only_the_beneficiary_can_withdraw() {
+    let (mut h, _) = setup_grant().await;
+    h.advance_clock(DURATION).await;
+
+    let impostor = Keypair::new();
+    let err = instruction_error(withdraw_vested(&mut h, &impostor, 1).await);
+    assert_eq!(err, custom(DepositError::VestingMismatch));
+}
+
+#[tokio::test]
+async fn invalid_or_duplicate_schedules_are_rejected() {
+    let (mut h, beneficiary) = setup_grant().await;
+    let funder = h.user.insecure_clone();
+
+    let ix = instruction::deposit_vested(
+        &h.program_id,
+        &funder.pubkey(),
+        &beneficiary.pubkey(),
+        LAMPORTS_PER_SOL,
+        0,
+        0,
+        DURATION,
+    );
+    let err = instruction_error(h.process(&[ix], &[&funder]).await);
+    assert_eq!(err, InstructionError::AccountAlreadyInitialized);
+
+    let ix = instruction::deposit_vested(
+        &h.program_id,
+        &funder.pubkey(),
+        &Keypair::new().pubkey(),
+        LAMPORTS_PER_SOL,
+        CLIFF,
+        0,
+        DURATION,
+    );
+    let err = instruction_error(h.process(&[ix], &[&funder]).await);
+    assert_eq!(err, InstructionError::InvalidArgument);
+}
@@ -0,0 +1,198 @@
+use solana_program::program_error::ProgramError;
+use std::fmt;
+
+/// Errors returned by the deposit program as `ProgramError::Custom(code)`.
+///
+/// Codes are part of the wire format: new variants get new numbers and
+/// existing numbers are never reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepositError {
+    /// The vault account is not the program's vault PDA.
+    VaultMismatch = 0,
+    /// The ledger account is not the signer's ledger PDA.
+    LedgerMismatch = 1,
+    /// The ledger has not been created by a deposit yet.
+    LedgerNotFound = 2,
+    /// The withdrawal exceeds the balance recorded in the ledger.
+    LedgerOverdrawn = 3,
+    /// A balance update overflowed.
+    Overflow = 4,
+    /// The config account is not the program's config PDA.
+    ConfigMismatch = 5,
+    /// `Initialize` was called on an existing config.
+    AlreadyInitialized = 6,
+    /// The program has not been initialized yet.
+    NotInitialized = 7,
+    /// The signer is not the admin (or nominated admin) in the config.
+    Unauthorized = 8,
+    /// The program is paused by the admin.
+    Paused = 9,
+    /// The pending withdrawal has not reached its unlock time.
+    WithdrawalLocked = 10,
+    /// Direct withdrawals are disabled while a withdrawal delay is configured.
+    TimelockRequired = 11,
+    /// The pending withdrawal account does not belong to the user.
+    WithdrawalMismatch = 12,
+    /// The token vault is not the vault's associated token account for the mint.
+    TokenVaultMismatch = 13,
+    /// The token ledger is not the user's ledger PDA for the mint.
+    TokenLedgerMismatch = 14,
+    /// The fee on the amount is larger than the amount itself.
+    AmountBelowFee = 15,
+    /// The ledger only pays out through approved multisig proposals.
+    MultisigRequired = 16,
+    /// The multisig account is not the ledger owner's multisig PDA.
+    MultisigMismatch = 17,
+    /// The signer is not part of the ledger's multisig.
+    NotMultisigSigner = 18,
+    /// The proposal account does not belong to the multisig.
+    ProposalMismatch = 19,
+    /// The proposal is past its expiry.
+    ProposalExpired = 20,
+    /// The proposal has fewer approvals than the multisig threshold.
+    ThresholdNotMet = 21,
+    /// The signer has already approved the proposal.
+    AlreadyApproved = 22,
+    /// The vesting schedule account does not belong to the beneficiary.
+    VestingMismatch = 23,
+    /// The withdrawal exceeds the vested, not yet withdrawn amount.
+    VestingLocked = 24,
+    /// The escrow account does not belong to the depositor or counterparty.
+    EscrowMismatch = 25,
+    /// The depositor has not released the escrow yet.
+    EscrowNotReleased = 26,
+    /// The escrow was released to the counterparty and can no longer be refunded.
+    EscrowReleased = 27,
+    /// The escrow timeout has not passed yet.
+    EscrowLocked = 28,
+    /// The deposit exceeds the per-transaction limit.
+    DepositTooLarge = 29,
+    /// The deposit would take the user's balance over the per-user cap.
+    UserCapExceeded = 30,
+    /// The deposit would take the vault over the total value locked cap.
+    TvlCapExceeded = 31,
+    /// The depositor is not on the allowlist.
+    NotAllowlisted = 32,
+    /// The delegation account is not the PDA of the given owner and delegate.
+    DelegationMismatch = 33,
+    /// The delegation has passed its expiry.
+    DelegationExpired = 34,
+    /// The withdrawal exceeds the delegate's remaining allowance.
+    AllowanceExceeded = 35,
+    /// The ledger cannot be closed while funds are locked or left in it.
+    FundsLocked = 36,
+    /// The share mint is not the program's share mint PDA.
+    ShareMintMismatch = 38,
+    /// There are no deposits to distribute rewards over.
+    NoDeposits = 39,
+    /// A ledger can only be closed together with all of its delegations.
+    DelegationsOpen = 40,
+    /// The allowlist marker is not the PDA of the address.
+    AllowlistMismatch = 41,
+}
+
+impl DepositError {
+    pub fn from_code(code: u32) -> Option<Self> {
+        Some(match code {
+            0 => Self::VaultMismatch,
+            1 => Self::LedgerMismatch,
+            2 => Self::LedgerNotFound,
+            3 => Self::LedgerOverdrawn,
+            4 => Self::Overflow,
+            5 => Self::ConfigMismatch,
+            6 => Self::AlreadyInitialized,
+            7 => Self::NotInitialized,
+            8 => Self::Unauthorized,
+            9 => Self::Paused,
+            10 => Self::WithdrawalLocked,
+            11 => Self::TimelockRequired,
+            12 => Self::WithdrawalMismatch,
+            13 => Self::TokenVaultMismatch,
+            14 => Self::TokenLedgerMismatch,
+            15 => Self::AmountBelowFee,
+            16 => Self::MultisigRequired,
+            17 => Self::MultisigMismatch,
+            18 => Self::NotMultisigSigner,
+            19 => Self::ProposalMismatch,
+            20 => Self::ProposalExpired,
+            21 => Self::ThresholdNotMet,
+            22 => Self::AlreadyApproved,
+            23 => Self::VestingMismatch,
+            24 => Self::VestingLocked,
+            25 => Self::EscrowMismatch,
+            26 => Self::EscrowNotReleased,
+            27 => Self::EscrowReleased,
+            28 => Self::EscrowLocked,
+            29 => Self::DepositTooLarge,
+            30 => Self::UserCapExceeded,
+            31 => Self::TvlCapExceeded,
+            32 => Self::NotAllowlisted,
+            33 => Self::DelegationMismatch,
+            34 => Self::DelegationExpired,
+            35 => Self::AllowanceExceeded,
+            36 => Self::FundsLocked,
+            38 => Self::ShareMintMismatch,
+            39 => Self::NoDeposits,
+            40 => Self::DelegationsOpen,
+            41 => Self::AllowlistMismatch,
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for DepositError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            Self::VaultMismatch => "vault account does not match the program vault",
+            Self::LedgerMismatch => "ledger account does not belong to the user",
+            Self::LedgerNotFound => "no ledger exists for this user, deposit first",
+            Self::LedgerOverdrawn => "amount exceeds the ledger balance",
+            Self::Overflow => "balance arithmetic overflowed",
+            Self::ConfigMismatch => "config account does not match the program config",
+            Self::AlreadyInitialized => "program is already initialized",
+            Self::NotInitialized => "program is not initialized",
+            Self::Unauthorized => "signer is not the program authority",
+            Self::Paused => "program is paused",
+            Self::WithdrawalLocked => "withdrawal is still time-locked",
+            Self::TimelockRequired => "withdrawals are time-locked, use request-withdraw",
+            Self::WithdrawalMismatch => "pending withdrawal does not belong to the user",
+            Self::TokenVaultMismatch => "token vault does not match the vault for this mint",
+            Self::TokenLedgerMismatch => "token ledger does not belong to the user and mint",
+            Self::AmountBelowFee => "amount is smaller than the fee",
+            Self::MultisigRequired => "ledger withdrawals require multisig approval",
+            Self::MultisigMismatch => "multisig account does not belong to the ledger owner",
+            Self::NotMultisigSigner => "signer is not part of the multisig",
+            Self::ProposalMismatch => "proposal does not belong to the multisig",
+            Self::ProposalExpired => "proposal has expired",
+            Self::ThresholdNotMet => "proposal has not reached the approval threshold",
+            Self::AlreadyApproved => "signer already approved the proposal",
+            Self::VestingMismatch => "vesting schedule does not belong to the beneficiary",
+            Self::VestingLocked => "amount exceeds the vested balance",
+            Self::EscrowMismatch => "escrow does not belong to the signer",
+            Self::EscrowNotReleased => "escrow has not been released by the depositor",
+            Self::EscrowReleased => "escrow was already released to the counterparty",
+            Self::EscrowLocked => "escrow cannot be refunded before its timeout",
+            Self::DepositTooLarge => "deposit exceeds the per-transaction limit",
+            Self::UserCapExceeded => "deposit exceeds the per-user balance cap",
+            Self::TvlCapExceeded => "deposit exceeds the total value locked cap",
+            Self::NotAllowlisted => "depositor is not on the allowlist",
+            Self::DelegationMismatch => "delegation account does not match owner and delegate",
+            Self::DelegationExpired => "delegation has expired",
+            Self::AllowanceExceeded => "withdrawal exceeds the delegate allowance",
+            Self::FundsLocked => "account still has pending withdrawals or token balances",
+            Self::ShareMintMismatch => "share mint does not match the program share mint",
+            Self::NoDeposits => "no deposits to distribute rewards over",
+            Self::DelegationsOpen => "every delegation of the ledger must be closed with it",
+            Self::AllowlistMismatch => "allowlist marker does not match the address",
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for DepositError {}
+
+impl From<DepositError> for ProgramError {
+    fn from(e: DepositError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
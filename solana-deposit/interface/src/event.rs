@@ -0,0 +1,105 @@
+//! Structured records the program logs with `sol_log_data`, one per action.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+/// Layout version written as the first byte of every event.
+pub const EVENT_VERSION: u8 = 1;
+
+/// What an [`Event`] records.
+///
+/// Borsh encodes the variant as a one-byte tag, so new kinds must only ever
+/// be appended. Unless noted, `user` is the signer, `amount` the lamports or
+/// token base units moved and `balance` the user's ledger balance afterwards;
+/// admin actions leave `amount` and `balance` at zero.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    Deposit,
+    Withdraw,
+    /// `amount` moved into a pending withdrawal.
+    RequestWithdraw,
+    ClaimWithdraw,
+    CancelWithdraw,
+    /// `balance` is the token ledger balance.
+    DepositToken,
+    /// `balance` is the token ledger balance.
+    WithdrawToken,
+    /// `user` is the ledger owner.
+    ExecuteWithdraw,
+    /// `user` is the beneficiary; `balance` is what the schedule still holds.
+    DepositVested,
+    /// `balance` is what the schedule still holds.
+    WithdrawVested,
+    /// `balance` is the escrowed amount after fees.
+    EscrowCreate,
+    EscrowClaim,
+    EscrowRefund,
+    /// `amount` is the withdrawn balance.
+    CloseAccount,
+    ClaimRewards,
+    CollectFees,
+    Initialize,
+    /// `user` is the nominated authority.
+    SetAuthority,
+    AcceptAuthority,
+    /// `amount` is 1 when pausing and 0 when resuming.
+    SetPaused,
+    /// `amount` is the delay in seconds.
+    SetWithdrawDelay,
+    SetFees,
+    SetLimits,
+    /// `amount` is 1 when enabling and 0 when disabling.
+    SetAllowlistEnabled,
+    /// `user` is the allowlisted address.
+    AllowlistAdd,
+    /// `user` is the removed address.
+    AllowlistRemove,
+    CreateShareMint,
+    /// `balance` is the total deposited the rewards are shared over.
+    DistributeRewards,
+    /// `amount` is the delegate's allowance.
+    ApproveDelegate,
+    RevokeDelegate,
+    /// `amount` is the approval threshold.
+    SetMultisig,
+    /// `user` is the ledger owner; `amount` is the proposed withdrawal.
+    ProposeWithdraw,
+    /// `user` is the ledger owner; `amount` is the proposed withdrawal.
+    ApproveWithdraw,
+    /// `user` is the ledger owner; `amount` is the proposed withdrawal.
+    CancelProposal,
+    /// `amount` is the escrowed amount now claimable by the counterparty.
+    EscrowRelease,
+    /// `user` is the holder; `amount` is the lamports the shares were worth
+    /// and `balance` the shares burned.
+    RedeemShares,
+}
+
+/// A program action as logged for indexers.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub kind: EventKind,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub balance: u64,
+    pub slot: u64,
+}
+
+impl Event {
+    /// Encodes the event behind its version byte.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut data = vec![EVENT_VERSION];
+        // Writing into a Vec cannot fail.
+        self.serialize(&mut data).unwrap();
+        data
+    }
+
+    /// Decodes an event written by [`Event::encode`], or `None` for unknown
+    /// versions and other data.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        match data.split_first() {
+            Some((&EVENT_VERSION, mut rest)) => Self::deserialize(&mut rest).ok(),
+            _ => None,
+        }
+    }
+}
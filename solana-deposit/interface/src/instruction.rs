@@ -0,0 +1,1067 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+
+use crate::{
+    find_allowlist_address, find_config_address, find_delegation_address, find_escrow_address,
+    find_ledger_address, find_multisig_address, find_program_data_address, find_proposal_address,
+    find_share_mint_address, find_token_ledger_address, find_vault_address, find_vesting_address,
+    find_withdrawal_address, get_share_account_address, get_token_vault_address,
+};
+
+/// Instructions understood by the deposit program.
+///
+/// Borsh encodes the variant as a one-byte tag followed by its fields, so new
+/// variants must only ever be appended to keep existing tags stable.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub enum ProgramInstruction {
+    /// Moves `amount` lamports from the user into the vault and credits their
+    /// ledger with `amount` minus the deposit fee.
+    ///
+    /// Once the share mint exists, mints receipt shares for those lamports at
+    /// the current exchange rate instead of crediting the ledger, except for
+    /// multisig ledgers. The shares are then the only claim on the lamports;
+    /// whoever holds them redeems them with [`ProgramInstruction::RedeemShares`].
+    ///
+    /// Accounts:
+    /// 0. `[signer, writable]` user
+    /// 1. `[writable]` vault PDA
+    /// 2. `[writable]` user ledger PDA, created on first deposit
+    /// 3. `[]` system program
+    /// 4. `[writable]` config PDA, accrues fees
+    /// 5. `[]` user allowlist marker PDA, required while the allowlist is enabled
+    /// 6. `[writable]` share mint PDA, required once shares are enabled
+    /// 7. `[writable]` user share token account, the associated one
+    /// 8. `[]` token program
+    Deposit { amount: u64 },
+    /// Debits `amount` from the user's ledger and pays it out of the vault,
+    /// minus the withdrawal fee.
+    ///
+    /// The user either signs, or a delegate signs and spends its allowance;
+    /// the user receives the lamports either way.
+    ///
+    /// Accounts: 0-4 of [`ProgramInstruction::Deposit`], the user not signing
+    /// when delegated, then, only when the user does not sign:
+    /// 5. `[signer]` delegate
+    /// 6. `[writable]` delegation PDA of user and delegate
+    Withdraw { amount: u64 },
+    /// Creates the config PDA with the signer as admin and funds the vault
+    /// PDA up to its rent-exempt minimum, which stays in the vault for good.
+    /// Only the program's upgrade authority may initialize.
+    ///
+    /// Accounts:
+    /// 0. `[signer, writable]` admin (upgrade authority), pays for the config account and vault reserve
+    /// 1. `[writable]` config PDA
+    /// 2. `[]` system program
+    /// 3. `[writable]` vault PDA
+    /// 4. `[]` program data account of this program
+    Initialize,
+    /// Nominates `new_authority` as admin; it takes effect once accepted.
+    ///
+    /// Accounts:
+    /// 0. `[signer]` current admin
+    /// 1. `[writable]` config PDA
+    SetAuthority { new_authority: Pubkey },
+    /// Completes a transfer started by [`ProgramInstruction::SetAuthority`].
+    ///
+    /// Accounts:
+    /// 0. `[signer]` nominated admin
+    /// 1. `[writable]` config PDA
+    AcceptAuthority,
+    /// Pauses or resumes deposits; `allow_withdrawals` keeps exits open while paused.
+    ///
+    /// Accounts:
+    /// 0. `[signer]` admin
+    /// 1. `[writable]` config PDA
+    SetPaused {
+        paused: bool,
+        allow_withdrawals: bool,
+    },
+    /// Sets the delay between requesting and claiming a withdrawal.
+    ///
+    /// Accounts:
+    /// 0. `[signer]` admin
+    /// 1. `[writable]` config PDA
+    SetWithdrawDelay { seconds: i64 },
+    /// Debits the user's ledger into a new pending withdrawal that unlocks
+    /// after the configured delay.
+    ///
+    /// Accounts:
+    /// 0. `[signer, writable]` user
+    /// 1. `[writable]` user ledger PDA
+    /// 2. `[writable]` pending withdrawal PDA for the ledger's next id
+    /// 3. `[]` system program
+    /// 4. `[writable]` config PDA
+    RequestWithdraw { amount: u64 },
+    /// Pays out an unlocked pending withdrawal, minus the withdrawal fee, and
+    /// closes it.
+    ///
+    /// Accounts:
+    /// 0. `[signer, writable]` user
+    /// 1. `[writable]` vault PDA
+    /// 2. `[writable]` user ledger PDA
+    /// 3. `[writable]` pending withdrawal PDA
+    /// 4. `[]` system program
+    /// 5. `[writable]` config PDA, accrues fees
+    ClaimWithdraw,
+    /// Returns a pending withdrawal to the user's ledger and closes it.
+    ///
+    /// Accounts:
+    /// 0. `[signer, writable]` user
+    /// 1. `[writable]` user ledger PDA
+    /// 2. `[writable]` pending withdrawal PDA
+    /// 3. `[writable]` config PDA
+    CancelWithdraw,
+    /// Moves `amount` base units of an SPL token into the vault's associated
+    /// token account for the mint and credits the user's token ledger.
+    ///
+    /// Accounts:
+    /// 0. `[signer, writable]` user
+    /// 1. `[writable]` user token account
+    /// 2. `[writable]` vault token account, created on first deposit of the mint
+    /// 3. `[writable]` user token ledger PDA, created on first deposit of the mint
+    /// 4. `[]` mint
+    /// 5. `[]` vault PDA
+    /// 6. `[]` config PDA
+    /// 7. `[]` token program
+    /// 8. `[]` associated token account program
+    /// 9. `[]` system program
+    /// 10. `[]` user allowlist marker PDA, required while the allowlist is enabled
+    DepositToken { amount: u64 },
+    /// Debits the user's token ledger and pays `amount` base units out of the
+    /// vault token account. Withdrawal time locks only apply to SOL.
+    ///
+    /// Accounts: 0-9 of [`ProgramInstruction::DepositToken`].
+    WithdrawToken { amount: u64 },
+    /// Sets the SOL deposit and withdrawal fees and who may collect them.
+    /// Either fee is at most [`crate::state::MAX_FEE_BPS`]. Fees stay in the
+    /// vault until collected; token transfers are not charged.
+    ///
+    /// Accounts:
+    /// 0. `[signer]` admin
+    /// 1. `[writable]` config PDA
+    SetFees {
+        deposit_fee_bps: u16,
+        withdraw_fee_bps: u16,
+        min_fee: u64,
+        fee_collector: Pubkey,
+    },
+    /// Pays all accrued fees out of the vault to the fee collector.
+    ///
+    /// Accounts:
+    /// 0. `[signer, writable]` fee collector
+    /// 1. `[writable]` vault PDA
+    /// 2. `[writable]` config PDA
+    /// 3. `[]` system program
+    CollectFees,
+    /// Puts the owner's ledger under an M-of-N multisig. From then on SOL only
+    /// leaves the ledger through executed proposals; the setting is permanent
+    /// and cannot be changed. The threshold must leave at least one spare
+    /// signer (M < N), so losing one key does not lock the ledger for good.
+    /// Rewards earned so far join the balance, and the ledger no longer earns
+    /// rewards.
+    ///
+    /// Accounts:
+    /// 0. `[signer, writable]` ledger owner, pays for the multisig account
+    /// 1. `[writable]` owner ledger PDA
+    /// 2. `[writable]` multisig PDA
+    /// 3. `[]` system program
+    /// 4. `[writable]` config PDA
+    SetMultisig { signers: Vec<Pubkey>, threshold: u8 },
+    /// Proposes paying `amount` from the multisig ledger to `recipient`; the
+    /// proposer's approval is recorded. Proposals expire after `expires_in`
+    /// seconds and honour the configured withdrawal delay.
+    ///
+    /// Accounts:
+    /// 0. `[signer, writable]` proposer, a multisig signer paying for the proposal
+    /// 1. `[writable]` multisig PDA
+    /// 2. `[writable]` proposal PDA for the multisig's next id
+    /// 3. `[]` system program
+    /// 4. `[]` config PDA
+    ProposeWithdraw {
+        amount: u64,
+        recipient: Pubkey,
+        expires_in: i64,
+    },
+    /// Records the signer's approval of a proposal.
+    ///
+    /// Accounts:
+    /// 0. `[signer]` multisig signer
+    /// 1. `[]` multisig PDA
+    /// 2. `[writable]` proposal PDA
+    ApproveWithdraw,
+    /// Pays out a proposal that reached the threshold and closes it. Anyone
+    /// may execute.
+    ///
+    /// Accounts:
+    /// 0. `[]` multisig PDA
+    /// 1. `[writable]` proposal PDA
+    /// 2. `[writable]` owner ledger PDA
+    /// 3. `[writable]` vault PDA
+    /// 4. `[writable]` recipient
+    /// 5. `[writable]` proposer, receives the proposal rent
+    /// 6. `[]` system program
+    /// 7. `[writable]` config PDA, accrues fees
+    ExecuteWithdraw,
+    /// Closes a proposal without paying it out. The proposer and the ledger
+    /// owner may cancel at any time, anyone once it has expired.
+    ///
+    /// Accounts:
+    /// 0. `[signer]` proposer, ledger owner, or anyone after expiry
+    /// 1. `[]` multisig PDA
+    /// 2. `[writable]` proposal PDA
+    /// 3. `[writable]` proposer, receives the proposal rent
+    CancelProposal,
+    /// Moves `amount` lamports from the funder into the vault, locked in a
+    /// vesting schedule for `beneficiary`. One schedule per funder and
+    /// beneficiary; the deposit fee is taken up front.
+    ///
+    /// Accounts:
+    /// 0. `[signer, writable]` funder, pays for the schedule account
+    /// 1. `[writable]` vault PDA
+    /// 2. `[writable]` vesting schedule PDA
+    /// 3. `[]` system program
+    /// 4. `[writable]` config PDA, accrues fees
+    /// 5. `[]` funder allowlist marker PDA, required while the allowlist is enabled
+    DepositVested {
+        amount: u64,
+        beneficiary: Pubkey,
+        start: i64,
+        cliff: i64,
+        end: i64,
+    },
+    /// Pays up to the vested, not yet withdrawn amount of a schedule to its
+    /// beneficiary, minus the withdrawal fee. The schedule is closed once
+    /// fully withdrawn. Not subject to the withdrawal delay.
+    ///
+    /// Accounts:
+    /// 0. `[signer, writable]` beneficiary
+    /// 1. `[writable]` funder, receives the schedule rent on close
+    /// 2. `[writable]` vault PDA
+    /// 3. `[writable]` vesting schedule PDA
+    /// 4. `[]` system program
+    /// 5. `[writable]` config PDA, accrues fees
+    WithdrawVested { amount: u64 },
+    /// Locks `amount` lamports (minus the deposit fee) in the vault for
+    /// `counterparty`. The depositor can reclaim them after `timeout` seconds
+    /// unless they have been released.
+    ///
+    /// Accounts:
+    /// 0. `[signer, writable]` depositor, pays for the escrow account
+    /// 1. `[writable]` vault PDA
+    /// 2. `[writable]` escrow PDA for `id`
+    /// 3. `[]` system program
+    /// 4. `[writable]` config PDA, accrues fees
+    /// 5. `[]` depositor allowlist marker PDA, required while the allowlist is enabled
+    EscrowCreate {
+        id: u64,
+        amount: u64,
+        counterparty: Pubkey,
+        timeout: i64,
+    },
+    /// Lets the counterparty claim the escrow.
+    ///
+    /// Accounts:
+    /// 0. `[signer]` depositor
+    /// 1. `[writable]` escrow PDA
+    EscrowRelease,
+    /// Pays a released escrow to the counterparty, minus the withdrawal fee,
+    /// and closes it.
+    ///
+    /// Accounts:
+    /// 0. `[signer, writable]` counterparty
+    /// 1. `[writable]` depositor, receives the escrow rent
+    /// 2. `[writable]` vault PDA
+    /// 3. `[writable]` escrow PDA
+    /// 4. `[]` system program
+    /// 5. `[writable]` config PDA, accrues fees
+    EscrowClaim,
+    /// Returns an unreleased escrow past its timeout to the depositor, minus
+    /// the withdrawal fee, and closes it.
+    ///
+    /// Accounts:
+    /// 0. `[signer, writable]` depositor
+    /// 1. `[writable]` vault PDA
+    /// 2. `[writable]` escrow PDA
+    /// 3. `[]` system program
+    /// 4. `[writable]` config PDA, accrues fees
+    EscrowRefund,
+    /// Sets the deposit limits; zero disables a limit. `max_deposit` and
+    /// `max_tvl` apply to every SOL deposit, `max_user_balance` to ledgers.
+    /// `max_tvl` caps ledger balances plus escrowed, vesting and share pool
+    /// lamports.
+    ///
+    /// Accounts:
+    /// 0. `[signer]` admin
+    /// 1. `[writable]` config PDA
+    SetLimits {
+        max_deposit: u64,
+        max_user_balance: u64,
+        max_tvl: u64,
+    },
+    /// Turns allowlist enforcement on deposits on or off.
+    ///
+    /// Accounts:
+    /// 0. `[signer]` admin
+    /// 1. `[writable]` config PDA
+    SetAllowlistEnabled { enabled: bool },
+    /// Adds `address` to the deposit allowlist by creating its marker PDA.
+    ///
+    /// Accounts:
+    /// 0. `[signer, writable]` admin, pays for the marker
+    /// 1. `[]` config PDA
+    /// 2. `[writable]` allowlist marker PDA of `address`
+    /// 3. `[]` system program
+    AllowlistAdd { address: Pubkey },
+    /// Removes `address` from the deposit allowlist by closing its marker PDA.
+    ///
+    /// Accounts:
+    /// 0. `[signer, writable]` admin, receives the marker rent
+    /// 1. `[]` config PDA
+    /// 2. `[writable]` allowlist marker PDA of `address`
+    AllowlistRemove { address: Pubkey },
+    /// Lets `delegate` withdraw up to `allowance` lamports from the owner's
+    /// ledger until `expiry` (0 for none), replacing any previous delegation.
+    ///
+    /// Accounts:
+    /// 0. `[signer, writable]` owner, pays for the delegation
+    /// 1. `[writable]` owner ledger PDA, counts its delegations
+    /// 2. `[writable]` delegation PDA of owner and delegate
+    /// 3. `[]` system program
+    ApproveDelegate {
+        delegate: Pubkey,
+        allowance: u64,
+        expiry: i64,
+    },
+    /// Closes a delegation, returning its rent to the owner.
+    ///
+    /// Accounts:
+    /// 0. `[signer, writable]` owner
+    /// 1. `[writable]` owner ledger PDA
+    /// 2. `[writable]` delegation PDA
+    RevokeDelegate,
+    /// Withdraws the whole ledger balance minus the withdrawal fee, then
+    /// closes the ledger and any listed token ledgers and delegations of the
+    /// user, returning their rent to the user.
+    ///
+    /// Refused while withdrawals are pending, a listed token ledger still
+    /// holds tokens, a delegation of the ledger is left out, or the ledger is
+    /// under a multisig.
+    ///
+    /// Accounts: 0-4 of [`ProgramInstruction::Deposit`], then any number of
+    /// `[writable]` empty token ledger PDAs and every delegation PDA of the
+    /// user.
+    CloseAccount,
+    /// Creates the receipt share mint PDA, with 9 decimals like SOL, and
+    /// enables share minting on deposits.
+    ///
+    /// Accounts:
+    /// 0. `[signer, writable]` admin, pays for the mint
+    /// 1. `[writable]` config PDA
+    /// 2. `[writable]` share mint PDA
+    /// 3. `[]` token program
+    /// 4. `[]` system program
+    CreateShareMint,
+    /// Moves `amount` lamports from the admin into the vault as rewards,
+    /// shared over every ledger outside a multisig and the receipt share pool
+    /// in proportion to their balances. The pool's part raises the value of
+    /// every share.
+    ///
+    /// Accounts:
+    /// 0. `[signer, writable]` admin
+    /// 1. `[writable]` vault PDA
+    /// 2. `[writable]` config PDA
+    /// 3. `[]` system program
+    DistributeRewards { amount: u64 },
+    /// Pays the user every reward their ledger has earned so far; refused for
+    /// multisig ledgers, which earn none.
+    ///
+    /// Accounts: 0-4 of [`ProgramInstruction::Deposit`].
+    ClaimRewards,
+    /// Burns `shares` receipt shares from a token account the signer owns or
+    /// is an approved delegate of, and pays the signer their value at the
+    /// current exchange rate minus the withdrawal fee. Receipts are liquid:
+    /// the withdrawal delay only applies to ledgers.
+    ///
+    /// Accounts:
+    /// 0. `[signer, writable]` holder
+    /// 1. `[writable]` vault PDA
+    /// 2. `[]` system program
+    /// 3. `[writable]` config PDA, accrues fees
+    /// 4. `[writable]` share mint PDA
+    /// 5. `[writable]` share token account to burn from
+    /// 6. `[]` token program
+    RedeemShares { shares: u64 },
+}
+
+impl ProgramInstruction {
+    pub fn pack(&self) -> Vec<u8> {
+        self.try_to_vec()
+            .expect("instruction serialization is infallible")
+    }
+}
+
+pub fn deposit(program_id: &Pubkey, user: &Pubkey, amount: u64) -> Instruction {
+    let mut accounts = user_accounts(program_id, user);
+    accounts.push(allowlist_account(program_id, user));
+    accounts.extend(share_accounts(program_id, user));
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::Deposit { amount }.pack(),
+        accounts,
+    )
+}
+
+pub fn withdraw(program_id: &Pubkey, user: &Pubkey, amount: u64) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::Withdraw { amount }.pack(),
+        user_accounts(program_id, user),
+    )
+}
+
+pub fn initialize(program_id: &Pubkey, admin: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::Initialize.pack(),
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(find_vault_address(program_id).0, false),
+            AccountMeta::new_readonly(find_program_data_address(program_id).0, false),
+        ],
+    )
+}
+
+pub fn set_authority(program_id: &Pubkey, admin: &Pubkey, new_authority: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::SetAuthority {
+            new_authority: *new_authority,
+        }
+        .pack(),
+        admin_accounts(program_id, admin),
+    )
+}
+
+pub fn accept_authority(program_id: &Pubkey, new_authority: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::AcceptAuthority.pack(),
+        admin_accounts(program_id, new_authority),
+    )
+}
+
+pub fn set_paused(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    paused: bool,
+    allow_withdrawals: bool,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::SetPaused {
+            paused,
+            allow_withdrawals,
+        }
+        .pack(),
+        admin_accounts(program_id, admin),
+    )
+}
+
+pub fn set_withdraw_delay(program_id: &Pubkey, admin: &Pubkey, seconds: i64) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::SetWithdrawDelay { seconds }.pack(),
+        admin_accounts(program_id, admin),
+    )
+}
+
+/// `id` must be the ledger's current `next_withdrawal_id`.
+pub fn request_withdraw(program_id: &Pubkey, user: &Pubkey, id: u64, amount: u64) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::RequestWithdraw { amount }.pack(),
+        vec![
+            AccountMeta::new(*user, true),
+            AccountMeta::new(find_ledger_address(program_id, user).0, false),
+            AccountMeta::new(find_withdrawal_address(program_id, user, id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(find_config_address(program_id).0, false),
+        ],
+    )
+}
+
+pub fn claim_withdraw(program_id: &Pubkey, user: &Pubkey, id: u64) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::ClaimWithdraw.pack(),
+        vec![
+            AccountMeta::new(*user, true),
+            AccountMeta::new(find_vault_address(program_id).0, false),
+            AccountMeta::new(find_ledger_address(program_id, user).0, false),
+            AccountMeta::new(find_withdrawal_address(program_id, user, id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(find_config_address(program_id).0, false),
+        ],
+    )
+}
+
+pub fn cancel_withdraw(program_id: &Pubkey, user: &Pubkey, id: u64) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::CancelWithdraw.pack(),
+        vec![
+            AccountMeta::new(*user, true),
+            AccountMeta::new(find_ledger_address(program_id, user).0, false),
+            AccountMeta::new(find_withdrawal_address(program_id, user, id).0, false),
+            AccountMeta::new(find_config_address(program_id).0, false),
+        ],
+    )
+}
+
+pub fn deposit_token(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    user_token_account: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let mut accounts = token_accounts(program_id, user, user_token_account, mint);
+    accounts.push(allowlist_account(program_id, user));
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::DepositToken { amount }.pack(),
+        accounts,
+    )
+}
+
+pub fn withdraw_token(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    user_token_account: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::WithdrawToken { amount }.pack(),
+        token_accounts(program_id, user, user_token_account, mint),
+    )
+}
+
+pub fn set_fees(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    deposit_fee_bps: u16,
+    withdraw_fee_bps: u16,
+    min_fee: u64,
+    fee_collector: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::SetFees {
+            deposit_fee_bps,
+            withdraw_fee_bps,
+            min_fee,
+            fee_collector: *fee_collector,
+        }
+        .pack(),
+        admin_accounts(program_id, admin),
+    )
+}
+
+pub fn collect_fees(program_id: &Pubkey, fee_collector: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::CollectFees.pack(),
+        vec![
+            AccountMeta::new(*fee_collector, true),
+            AccountMeta::new(find_vault_address(program_id).0, false),
+            AccountMeta::new(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn set_multisig(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    signers: &[Pubkey],
+    threshold: u8,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::SetMultisig {
+            signers: signers.to_vec(),
+            threshold,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(find_ledger_address(program_id, owner).0, false),
+            AccountMeta::new(find_multisig_address(program_id, owner).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(find_config_address(program_id).0, false),
+        ],
+    )
+}
+
+/// `id` must be the multisig's current `next_proposal_id`.
+pub fn propose_withdraw(
+    program_id: &Pubkey,
+    proposer: &Pubkey,
+    owner: &Pubkey,
+    id: u64,
+    amount: u64,
+    recipient: &Pubkey,
+    expires_in: i64,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::ProposeWithdraw {
+            amount,
+            recipient: *recipient,
+            expires_in,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(*proposer, true),
+            AccountMeta::new(find_multisig_address(program_id, owner).0, false),
+            AccountMeta::new(find_proposal_address(program_id, owner, id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        ],
+    )
+}
+
+pub fn approve_withdraw(
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    owner: &Pubkey,
+    id: u64,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::ApproveWithdraw.pack(),
+        vec![
+            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new_readonly(find_multisig_address(program_id, owner).0, false),
+            AccountMeta::new(find_proposal_address(program_id, owner, id).0, false),
+        ],
+    )
+}
+
+pub fn execute_withdraw(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    id: u64,
+    proposer: &Pubkey,
+    recipient: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::ExecuteWithdraw.pack(),
+        vec![
+            AccountMeta::new_readonly(find_multisig_address(program_id, owner).0, false),
+            AccountMeta::new(find_proposal_address(program_id, owner, id).0, false),
+            AccountMeta::new(find_ledger_address(program_id, owner).0, false),
+            AccountMeta::new(find_vault_address(program_id).0, false),
+            AccountMeta::new(*recipient, false),
+            AccountMeta::new(*proposer, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(find_config_address(program_id).0, false),
+        ],
+    )
+}
+
+pub fn cancel_proposal(
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    owner: &Pubkey,
+    id: u64,
+    proposer: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::CancelProposal.pack(),
+        vec![
+            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new_readonly(find_multisig_address(program_id, owner).0, false),
+            AccountMeta::new(find_proposal_address(program_id, owner, id).0, false),
+            AccountMeta::new(*proposer, false),
+        ],
+    )
+}
+
+pub fn deposit_vested(
+    program_id: &Pubkey,
+    funder: &Pubkey,
+    beneficiary: &Pubkey,
+    amount: u64,
+    start: i64,
+    cliff: i64,
+    end: i64,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::DepositVested {
+            amount,
+            beneficiary: *beneficiary,
+            start,
+            cliff,
+            end,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(*funder, true),
+            AccountMeta::new(find_vault_address(program_id).0, false),
+            AccountMeta::new(
+                find_vesting_address(program_id, funder, beneficiary).0,
+                false,
+            ),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(find_config_address(program_id).0, false),
+            allowlist_account(program_id, funder),
+        ],
+    )
+}
+
+pub fn withdraw_vested(
+    program_id: &Pubkey,
+    beneficiary: &Pubkey,
+    funder: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::WithdrawVested { amount }.pack(),
+        vec![
+            AccountMeta::new(*beneficiary, true),
+            AccountMeta::new(*funder, false),
+            AccountMeta::new(find_vault_address(program_id).0, false),
+            AccountMeta::new(
+                find_vesting_address(program_id, funder, beneficiary).0,
+                false,
+            ),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(find_config_address(program_id).0, false),
+        ],
+    )
+}
+
+pub fn escrow_create(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    id: u64,
+    amount: u64,
+    counterparty: &Pubkey,
+    timeout: i64,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::EscrowCreate {
+            id,
+            amount,
+            counterparty: *counterparty,
+            timeout,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(*depositor, true),
+            AccountMeta::new(find_vault_address(program_id).0, false),
+            AccountMeta::new(find_escrow_address(program_id, depositor, id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(find_config_address(program_id).0, false),
+            allowlist_account(program_id, depositor),
+        ],
+    )
+}
+
+pub fn escrow_release(program_id: &Pubkey, depositor: &Pubkey, id: u64) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::EscrowRelease.pack(),
+        vec![
+            AccountMeta::new_readonly(*depositor, true),
+            AccountMeta::new(find_escrow_address(program_id, depositor, id).0, false),
+        ],
+    )
+}
+
+pub fn escrow_claim(
+    program_id: &Pubkey,
+    counterparty: &Pubkey,
+    depositor: &Pubkey,
+    id: u64,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::EscrowClaim.pack(),
+        vec![
+            AccountMeta::new(*counterparty, true),
+            AccountMeta::new(*depositor, false),
+            AccountMeta::new(find_vault_address(program_id).0, false),
+            AccountMeta::new(find_escrow_address(program_id, depositor, id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(find_config_address(program_id).0, false),
+        ],
+    )
+}
+
+pub fn escrow_refund(program_id: &Pubkey, depositor: &Pubkey, id: u64) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::EscrowRefund.pack(),
+        vec![
+            AccountMeta::new(*depositor, true),
+            AccountMeta::new(find_vault_address(program_id).0, false),
+            AccountMeta::new(find_escrow_address(program_id, depositor, id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(find_config_address(program_id).0, false),
+        ],
+    )
+}
+
+pub fn set_limits(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    max_deposit: u64,
+    max_user_balance: u64,
+    max_tvl: u64,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::SetLimits {
+            max_deposit,
+            max_user_balance,
+            max_tvl,
+        }
+        .pack(),
+        admin_accounts(program_id, admin),
+    )
+}
+
+pub fn set_allowlist_enabled(program_id: &Pubkey, admin: &Pubkey, enabled: bool) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::SetAllowlistEnabled { enabled }.pack(),
+        admin_accounts(program_id, admin),
+    )
+}
+
+pub fn allowlist_add(program_id: &Pubkey, admin: &Pubkey, address: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::AllowlistAdd { address: *address }.pack(),
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new(find_allowlist_address(program_id, address).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn allowlist_remove(program_id: &Pubkey, admin: &Pubkey, address: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::AllowlistRemove { address: *address }.pack(),
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(find_config_address(program_id).0, false),
+            AccountMeta::new(find_allowlist_address(program_id, address).0, false),
+        ],
+    )
+}
+
+fn token_accounts(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    user_token_account: &Pubkey,
+    mint: &Pubkey,
+) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(*user, true),
+        AccountMeta::new(*user_token_account, false),
+        AccountMeta::new(get_token_vault_address(program_id, mint), false),
+        AccountMeta::new(find_token_ledger_address(program_id, user, mint).0, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(find_vault_address(program_id).0, false),
+        AccountMeta::new_readonly(find_config_address(program_id).0, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ]
+}
+
+/// Withdraws `amount` from `owner`'s ledger to `owner`, signed by `delegate`.
+pub fn withdraw_delegated(
+    program_id: &Pubkey,
+    delegate: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let mut accounts = user_accounts(program_id, owner);
+    accounts[0].is_signer = false;
+    accounts.push(AccountMeta::new_readonly(*delegate, true));
+    accounts.push(AccountMeta::new(
+        find_delegation_address(program_id, owner, delegate).0,
+        false,
+    ));
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::Withdraw { amount }.pack(),
+        accounts,
+    )
+}
+
+pub fn approve_delegate(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    delegate: &Pubkey,
+    allowance: u64,
+    expiry: i64,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::ApproveDelegate {
+            delegate: *delegate,
+            allowance,
+            expiry,
+        }
+        .pack(),
+        vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(find_ledger_address(program_id, owner).0, false),
+            AccountMeta::new(
+                find_delegation_address(program_id, owner, delegate).0,
+                false,
+            ),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn revoke_delegate(program_id: &Pubkey, owner: &Pubkey, delegate: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::RevokeDelegate.pack(),
+        vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(find_ledger_address(program_id, owner).0, false),
+            AccountMeta::new(
+                find_delegation_address(program_id, owner, delegate).0,
+                false,
+            ),
+        ],
+    )
+}
+
+pub fn close_account(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    mints: &[Pubkey],
+    delegates: &[Pubkey],
+) -> Instruction {
+    let mut accounts = user_accounts(program_id, user);
+    accounts.extend(
+        mints.iter().map(|mint| {
+            AccountMeta::new(find_token_ledger_address(program_id, user, mint).0, false)
+        }),
+    );
+    accounts.extend(delegates.iter().map(|delegate| {
+        AccountMeta::new(find_delegation_address(program_id, user, delegate).0, false)
+    }));
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::CloseAccount.pack(),
+        accounts,
+    )
+}
+
+pub fn create_share_mint(program_id: &Pubkey, admin: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::CreateShareMint.pack(),
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(find_config_address(program_id).0, false),
+            AccountMeta::new(find_share_mint_address(program_id).0, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn distribute_rewards(program_id: &Pubkey, admin: &Pubkey, amount: u64) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::DistributeRewards { amount }.pack(),
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(find_vault_address(program_id).0, false),
+            AccountMeta::new(find_config_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn claim_rewards(program_id: &Pubkey, user: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::ClaimRewards.pack(),
+        user_accounts(program_id, user),
+    )
+}
+
+/// Redeems `shares` from `holder`'s associated share token account.
+pub fn redeem_shares(program_id: &Pubkey, holder: &Pubkey, shares: u64) -> Instruction {
+    let [share_mint, share_account, token_program] = share_accounts(program_id, holder);
+    Instruction::new_with_bytes(
+        *program_id,
+        &ProgramInstruction::RedeemShares { shares }.pack(),
+        vec![
+            AccountMeta::new(*holder, true),
+            AccountMeta::new(find_vault_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(find_config_address(program_id).0, false),
+            share_mint,
+            share_account,
+            token_program,
+        ],
+    )
+}
+
+fn share_accounts(program_id: &Pubkey, user: &Pubkey) -> [AccountMeta; 3] {
+    [
+        AccountMeta::new(find_share_mint_address(program_id).0, false),
+        AccountMeta::new(get_share_account_address(program_id, user), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ]
+}
+
+fn allowlist_account(program_id: &Pubkey, depositor: &Pubkey) -> AccountMeta {
+    AccountMeta::new_readonly(find_allowlist_address(program_id, depositor).0, false)
+}
+
+fn admin_accounts(program_id: &Pubkey, admin: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new(find_config_address(program_id).0, false),
+    ]
+}
+
+fn user_accounts(program_id: &Pubkey, user: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(*user, true),
+        AccountMeta::new(find_vault_address(program_id).0, false),
+        AccountMeta::new(find_ledger_address(program_id, user).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(find_config_address(program_id).0, false),
+    ]
+}
@@ -0,0 +1,149 @@
+//! Wire format and account layouts shared by the deposit program and its client.
+
+pub mod error;
+pub mod event;
+pub mod instruction;
+pub mod state;
+
+use solana_program::{bpf_loader_upgradeable, pubkey::Pubkey};
+
+/// Seed of the program-owned vault that holds every deposited lamport.
+pub const VAULT_SEED: &[u8] = b"vault";
+
+/// Seed prefix of the per-user ledger account, followed by the user's key.
+pub const LEDGER_SEED: &[u8] = b"ledger";
+
+/// Seed of the global program configuration account.
+pub const CONFIG_SEED: &[u8] = b"config";
+
+/// Seed prefix of a pending time-locked withdrawal, followed by the user's
+/// key and the little-endian request id.
+pub const WITHDRAWAL_SEED: &[u8] = b"withdrawal";
+
+/// Seed prefix of a per-user, per-mint token ledger, followed by the user's
+/// key and the mint.
+pub const TOKEN_LEDGER_SEED: &[u8] = b"token_ledger";
+
+/// Seed prefix of a ledger's multisig signer set, followed by the owner's key.
+pub const MULTISIG_SEED: &[u8] = b"multisig";
+
+/// Seed prefix of a multisig withdrawal proposal, followed by the ledger
+/// owner's key and the little-endian proposal id.
+pub const PROPOSAL_SEED: &[u8] = b"proposal";
+
+/// Seed prefix of a vesting schedule, followed by the funder's and the
+/// beneficiary's keys.
+pub const VESTING_SEED: &[u8] = b"vesting";
+
+/// Seed prefix of an escrow, followed by the depositor's key and the
+/// little-endian escrow id.
+pub const ESCROW_SEED: &[u8] = b"escrow";
+
+/// Seed prefix of an allowlist marker, followed by the allowed address.
+pub const ALLOWLIST_SEED: &[u8] = b"allowlist";
+
+/// Seed of the receipt share mint, which is also its own mint authority.
+pub const SHARE_MINT_SEED: &[u8] = b"share_mint";
+
+/// Seed prefix of a withdraw delegation, followed by the owner's and the
+/// delegate's keys.
+pub const DELEGATION_SEED: &[u8] = b"delegation";
+
+pub fn find_vault_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_SEED], program_id)
+}
+
+pub fn find_ledger_address(program_id: &Pubkey, user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LEDGER_SEED, user.as_ref()], program_id)
+}
+
+pub fn find_config_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED], program_id)
+}
+
+pub fn find_token_ledger_address(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    mint: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[TOKEN_LEDGER_SEED, user.as_ref(), mint.as_ref()],
+        program_id,
+    )
+}
+
+/// Associated token account of the vault PDA that holds every deposit of `mint`.
+pub fn get_token_vault_address(program_id: &Pubkey, mint: &Pubkey) -> Pubkey {
+    spl_associated_token_account::get_associated_token_address(
+        &find_vault_address(program_id).0,
+        mint,
+    )
+}
+
+pub fn find_withdrawal_address(program_id: &Pubkey, user: &Pubkey, id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[WITHDRAWAL_SEED, user.as_ref(), &id.to_le_bytes()],
+        program_id,
+    )
+}
+
+pub fn find_multisig_address(program_id: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MULTISIG_SEED, owner.as_ref()], program_id)
+}
+
+pub fn find_proposal_address(program_id: &Pubkey, owner: &Pubkey, id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[PROPOSAL_SEED, owner.as_ref(), &id.to_le_bytes()],
+        program_id,
+    )
+}
+
+pub fn find_vesting_address(
+    program_id: &Pubkey,
+    funder: &Pubkey,
+    beneficiary: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[VESTING_SEED, funder.as_ref(), beneficiary.as_ref()],
+        program_id,
+    )
+}
+
+pub fn find_escrow_address(program_id: &Pubkey, depositor: &Pubkey, id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[ESCROW_SEED, depositor.as_ref(), &id.to_le_bytes()],
+        program_id,
+    )
+}
+
+pub fn find_allowlist_address(program_id: &Pubkey, address: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ALLOWLIST_SEED, address.as_ref()], program_id)
+}
+
+pub fn find_delegation_address(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    delegate: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[DELEGATION_SEED, owner.as_ref(), delegate.as_ref()],
+        program_id,
+    )
+}
+
+pub fn find_share_mint_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SHARE_MINT_SEED], program_id)
+}
+
+/// ProgramData account of the deployed program, which records its upgrade authority.
+pub fn find_program_data_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id())
+}
+
+/// Associated token account holding the receipt shares of `user`.
+pub fn get_share_account_address(program_id: &Pubkey, user: &Pubkey) -> Pubkey {
+    spl_associated_token_account::get_associated_token_address(
+        user,
+        &find_share_mint_address(program_id).0,
+    )
+}
@@ -0,0 +1,352 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{entrypoint::ProgramResult, msg, pubkey::Pubkey};
+
+use crate::error::DepositError;
+
+/// Per-user record of how many vault lamports belong to that user.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, PartialEq)]
+pub struct DepositAccount {
+    pub balance: u64,
+    /// Id assigned to the next `RequestWithdraw`.
+    pub next_withdrawal_id: u64,
+    /// Number of requested withdrawals not yet claimed or cancelled.
+    pub pending_withdrawals: u32,
+    /// Set once the owner configures a multisig; withdrawals then go through
+    /// approved proposals only.
+    pub multisig: bool,
+    /// `Config::reward_index` when rewards were last settled into `rewards`.
+    pub reward_index: u128,
+    /// Settled rewards not claimed yet, in lamports.
+    pub rewards: u64,
+    /// Delegations of this ledger not revoked yet; closing the ledger must
+    /// close every one of them.
+    pub delegations: u16,
+}
+
+impl DepositAccount {
+    pub const LEN: usize = 8 + 8 + 4 + 1 + 16 + 8 + 2;
+
+    /// Settled rewards plus those earned by `balance` since the last settlement.
+    /// Multisig ledgers are outside the reward base and earn nothing.
+    pub fn pending_rewards(&self, reward_index: u128) -> u64 {
+        if self.multisig {
+            return self.rewards;
+        }
+        let earned = self.balance as u128 * reward_index.saturating_sub(self.reward_index)
+            / REWARD_INDEX_SCALE;
+        self.rewards.saturating_add(earned as u64)
+    }
+
+    /// Moves the rewards earned so far into `rewards`; call before any change
+    /// to `balance`.
+    pub fn settle_rewards(&mut self, reward_index: u128) {
+        self.rewards = self.pending_rewards(reward_index);
+        self.reward_index = reward_index;
+    }
+
+    pub fn credit(&mut self, amount: u64) -> ProgramResult {
+        self.balance = self
+            .balance
+            .checked_add(amount)
+            .ok_or(DepositError::Overflow)?;
+        Ok(())
+    }
+
+    pub fn debit(&mut self, amount: u64) -> ProgramResult {
+        if self.balance < amount {
+            msg!("Insufficient funds: {} need: {}", self.balance, amount);
+            return Err(DepositError::LedgerOverdrawn.into());
+        }
+        self.balance -= amount;
+        Ok(())
+    }
+}
+
+/// Fixed-point scale of `Config::reward_index`.
+pub const REWARD_INDEX_SCALE: u128 = 1_000_000_000_000;
+
+/// 100% in basis points, the denominator of every bps fee.
+pub const BPS_DENOMINATOR: u16 = 10_000;
+
+/// 10%, the upper bound for `deposit_fee_bps` and `withdraw_fee_bps`.
+pub const MAX_FEE_BPS: u16 = 1_000;
+
+/// Global program settings, stored in the config PDA created by `Initialize`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, PartialEq)]
+pub struct Config {
+    pub admin: Pubkey,
+    /// Authority nominated by `SetAuthority`; `Pubkey::default()` when none.
+    pub pending_admin: Pubkey,
+    /// Emergency stop: rejects new deposits while set.
+    pub paused: bool,
+    /// Whether users may still withdraw while `paused` is set.
+    pub allow_withdrawals_when_paused: bool,
+    /// Seconds between `RequestWithdraw` and `ClaimWithdraw`; zero allows
+    /// immediate `Withdraw`.
+    pub withdraw_delay: i64,
+    /// Fee charged on deposits, in basis points of the deposited amount.
+    pub deposit_fee_bps: u16,
+    /// Fee charged on withdrawals, in basis points of the withdrawn amount.
+    pub withdraw_fee_bps: u16,
+    /// Floor applied to any non-zero basis-point fee, in lamports.
+    pub min_fee: u64,
+    /// Account allowed to collect the accrued fees.
+    pub fee_collector: Pubkey,
+    /// Fees held in the vault that have not been collected yet.
+    pub accrued_fees: u64,
+    /// Largest single deposit in lamports; zero means unlimited.
+    pub max_deposit: u64,
+    /// Largest ledger balance a user may reach by depositing; zero means unlimited.
+    pub max_user_balance: u64,
+    /// Largest total of ledger balances, locked lamports and the share pool
+    /// deposits may bring the program to; zero means unlimited.
+    pub max_tvl: u64,
+    /// Restricts SOL and token deposits to addresses with an allowlist marker.
+    pub allowlist_enabled: bool,
+    /// Set once `CreateShareMint` ran; deposits then mint receipt shares.
+    pub shares_enabled: bool,
+    /// Lamports backing the outstanding receipt shares, deposits and their
+    /// part of the rewards.
+    pub share_pool: u64,
+    /// Sum of every SOL ledger balance outside a multisig, the base rewards
+    /// are shared over.
+    pub total_deposits: u64,
+    /// Rewards distributed per deposited lamport so far, scaled by
+    /// [`REWARD_INDEX_SCALE`].
+    pub reward_index: u128,
+    /// Lamports held for escrows and vesting schedules, after deposit fees.
+    pub total_locked: u64,
+}
+
+impl Config {
+    pub const LEN: usize =
+        32 + 32 + 1 + 1 + 8 + 2 + 2 + 8 + 32 + 8 + 8 + 8 + 8 + 1 + 1 + 8 + 8 + 16 + 8;
+
+    /// Lamports deposited into ledgers, escrows, vesting schedules and the
+    /// share pool.
+    pub fn tvl(&self) -> u64 {
+        self.total_deposits
+            .saturating_add(self.total_locked)
+            .saturating_add(self.share_pool)
+    }
+
+    pub fn withdrawals_blocked(&self) -> bool {
+        self.paused && !self.allow_withdrawals_when_paused
+    }
+
+    pub fn deposit_fee(&self, amount: u64) -> u64 {
+        fee(amount, self.deposit_fee_bps, self.min_fee)
+    }
+
+    pub fn withdraw_fee(&self, amount: u64) -> u64 {
+        fee(amount, self.withdraw_fee_bps, self.min_fee)
+    }
+
+    /// Shares minted for depositing `amount` when `supply` shares exist,
+    /// rounded down; the first deposit mints one share per lamport.
+    pub fn shares_for_deposit(&self, amount: u64, supply: u64) -> u64 {
+        if supply == 0 || self.share_pool == 0 {
+            return amount;
+        }
+        (amount as u128 * supply as u128 / self.share_pool as u128) as u64
+    }
+
+    /// Lamports `shares` redeem for when `supply` shares exist, rounded down.
+    pub fn share_value(&self, shares: u64, supply: u64) -> u64 {
+        if supply == 0 {
+            return 0;
+        }
+        (shares as u128 * self.share_pool as u128 / supply as u128) as u64
+    }
+}
+
+/// `bps` of `amount`, raised to `min_fee`; zero when `bps` is zero.
+fn fee(amount: u64, bps: u16, min_fee: u64) -> u64 {
+    if bps == 0 {
+        return 0;
+    }
+    let fee = amount as u128 * bps as u128 / BPS_DENOMINATOR as u128;
+    (fee as u64).max(min_fee)
+}
+
+/// Per-user record of how many vault tokens of one mint belong to that user.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, PartialEq)]
+pub struct TokenDepositAccount {
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub balance: u64,
+}
+
+impl TokenDepositAccount {
+    pub const LEN: usize = 32 + 32 + 8;
+
+    pub fn credit(&mut self, amount: u64) -> ProgramResult {
+        self.balance = self
+            .balance
+            .checked_add(amount)
+            .ok_or(DepositError::Overflow)?;
+        Ok(())
+    }
+
+    pub fn debit(&mut self, amount: u64) -> ProgramResult {
+        if self.balance < amount {
+            msg!("Insufficient tokens: {} need: {}", self.balance, amount);
+            return Err(DepositError::LedgerOverdrawn.into());
+        }
+        self.balance -= amount;
+        Ok(())
+    }
+}
+
+/// A withdrawal moved out of a ledger and waiting for its time lock to expire.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, PartialEq)]
+pub struct PendingWithdrawal {
+    pub user: Pubkey,
+    pub id: u64,
+    pub amount: u64,
+    /// Unix timestamp from which the withdrawal can be claimed.
+    pub unlock_at: i64,
+}
+
+impl PendingWithdrawal {
+    pub const LEN: usize = 32 + 8 + 8 + 8;
+}
+
+/// Largest signer set of a multisig; approvals are tracked in a `u16` bitmap.
+pub const MAX_MULTISIG_SIGNERS: usize = 10;
+
+/// M-of-N signer set that must approve withdrawals from a ledger.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, PartialEq)]
+pub struct Multisig {
+    pub owner: Pubkey,
+    pub threshold: u8,
+    /// Id assigned to the next `ProposeWithdraw`.
+    pub next_proposal_id: u64,
+    pub signers: Vec<Pubkey>,
+}
+
+impl Multisig {
+    pub const LEN: usize = 32 + 1 + 8 + 4 + 32 * MAX_MULTISIG_SIGNERS;
+
+    pub fn signer_index(&self, key: &Pubkey) -> Option<usize> {
+        self.signers.iter().position(|signer| signer == key)
+    }
+}
+
+/// A withdrawal from a multisig ledger collecting approvals.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, PartialEq)]
+pub struct WithdrawalProposal {
+    /// Owner of the ledger the withdrawal is debited from.
+    pub owner: Pubkey,
+    pub id: u64,
+    /// Signer that paid for the proposal account and gets its rent back.
+    pub proposer: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    /// Bit `i` is set once `signers[i]` of the multisig has approved.
+    pub approvals: u16,
+    /// Unix timestamp from which the proposal can be executed.
+    pub unlock_at: i64,
+    /// Unix timestamp after which the proposal can no longer be executed.
+    pub expires_at: i64,
+}
+
+impl WithdrawalProposal {
+    pub const LEN: usize = 32 + 8 + 32 + 32 + 8 + 2 + 8 + 8;
+
+    pub fn approval_count(&self) -> u32 {
+        self.approvals.count_ones()
+    }
+
+    pub fn is_approved_by(&self, index: usize) -> bool {
+        self.approvals & (1 << index) != 0
+    }
+}
+
+/// Lamports granted by `funder` that unlock for `beneficiary` over time.
+///
+/// Nothing vests before `cliff`; from then on the grant vests linearly
+/// between `start` and `end`, so `cliff == end` is a pure cliff.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, PartialEq)]
+pub struct VestingSchedule {
+    pub funder: Pubkey,
+    pub beneficiary: Pubkey,
+    pub total: u64,
+    pub withdrawn: u64,
+    pub start: i64,
+    pub cliff: i64,
+    pub end: i64,
+}
+
+impl VestingSchedule {
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 8 + 8 + 8;
+
+    pub fn is_valid(&self) -> bool {
+        self.start <= self.cliff && self.cliff <= self.end
+    }
+
+    /// Portion of `total` vested at unix timestamp `now`.
+    pub fn vested_amount(&self, now: i64) -> u64 {
+        if now < self.cliff {
+            return 0;
+        }
+        if now >= self.end {
+            return self.total;
+        }
+        let elapsed = (now - self.start) as u128;
+        let duration = (self.end - self.start) as u128;
+        (self.total as u128 * elapsed / duration) as u64
+    }
+
+    /// Vested lamports not withdrawn yet.
+    pub fn withdrawable(&self, now: i64) -> u64 {
+        self.vested_amount(now).saturating_sub(self.withdrawn)
+    }
+}
+
+/// Lamports locked in the vault by `depositor` for `counterparty`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, PartialEq)]
+pub struct Escrow {
+    pub depositor: Pubkey,
+    pub counterparty: Pubkey,
+    pub id: u64,
+    pub amount: u64,
+    /// Set by the depositor; only then may the counterparty claim.
+    pub released: bool,
+    /// Unix timestamp from which an unreleased escrow can be refunded.
+    pub refund_after: i64,
+}
+
+impl Escrow {
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 1 + 8;
+}
+
+/// Marker proving `address` is on the deposit allowlist; exists only while allowed.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, PartialEq)]
+pub struct AllowlistEntry {
+    pub address: Pubkey,
+}
+
+impl AllowlistEntry {
+    pub const LEN: usize = 32;
+}
+
+/// Lets `delegate` withdraw up to `allowance` lamports from `owner`'s ledger
+/// on the owner's behalf.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, PartialEq)]
+pub struct Delegation {
+    pub owner: Pubkey,
+    pub delegate: Pubkey,
+    /// Lamports the delegate may still withdraw.
+    pub allowance: u64,
+    /// Unix timestamp after which the delegation is void, or 0 for none.
+    pub expiry: i64,
+}
+
+impl Delegation {
+    pub const LEN: usize = 32 + 32 + 8 + 8;
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expiry != 0 && now > self.expiry
+    }
+}